@@ -0,0 +1,95 @@
+// This is the alphabet of the "base64" encoding, as defined in [IETF RFC 4648
+// section 4](https://tools.ietf.org/html/rfc4648#section-4).
+const ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Determine the value of the given character of the base64 alphabet, or
+// `None` if the character is not in the alphabet.
+fn decode_character(c: u8) -> Option<u32> {
+    ALPHABET.iter().zip(0..).find_map(|(&alphabet_c, value)| {
+        if alphabet_c == c {
+            Some(value)
+        } else {
+            None
+        }
+    })
+}
+
+// Decode the given base64 text.  The padding at the end is optional, but
+// `None` is returned if the text contains any other character outside the
+// base64 alphabet, or has a length which no encoding could produce.
+pub(crate) fn decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim_end_matches('=').as_bytes();
+    if encoded.len() % 4 == 1 {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+    for group in encoded.chunks(4) {
+        let mut bits = 0;
+        for (i, &c) in group.iter().enumerate() {
+            bits |= decode_character(c)? << (18 - 6 * i);
+        }
+        let bytes = bits.to_be_bytes();
+        decoded.extend(&bytes[1..group.len()]);
+    }
+    Some(decoded)
+}
+
+// Encode the given bytes as base64 text, with padding.
+pub(crate) fn encode(decoded: &[u8]) -> String {
+    let mut encoded = String::with_capacity(decoded.len().div_ceil(3) * 4);
+    for group in decoded.chunks(3) {
+        let mut bits = 0;
+        for (i, &byte) in group.iter().enumerate() {
+            bits |= u32::from(byte) << (16 - 8 * i);
+        }
+        for i in 0..4 {
+            if i <= group.len() {
+                let value = (bits >> (18 - 6 * i)) & 0x3F;
+                encoded.push(char::from(ALPHABET[value as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // These are the test vectors from [IETF RFC 4648 section
+    // 10](https://tools.ietf.org/html/rfc4648#section-10).
+    const TEST_VECTORS: &[(&str, &str)] = &[
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn encode_test_vectors() {
+        for (decoded, encoded) in TEST_VECTORS {
+            assert_eq!(*encoded, encode(decoded.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn decode_test_vectors() {
+        for (decoded, encoded) in TEST_VECTORS {
+            assert_eq!(Some(decoded.as_bytes()), decode(encoded).as_deref());
+        }
+        assert_eq!(Some(&b"fo"[..]), decode("Zm8").as_deref());
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(None, decode("Zm9v!"));
+        assert_eq!(None, decode("Zm9vY"));
+    }
+}
@@ -0,0 +1,161 @@
+use super::{
+    is_tchar,
+    split_quoted,
+    unquote,
+};
+
+/// This represents one of the authentication challenges listed in a
+/// `WWW-Authenticate` header, as defined in [IETF RFC 7235 section
+/// 2.1](https://tools.ietf.org/html/rfc7235#section-2.1).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Challenge {
+    /// These are the parameters of the challenge, such as `realm`, as
+    /// name/value pairs in the order in which they appear.  Any quoting and
+    /// escaping has been removed from the values.
+    pub parameters: Vec<(String, String)>,
+
+    /// This is the authentication scheme of the challenge, such as `Basic`.
+    pub scheme: String,
+
+    /// This is the single opaque value which some authentication schemes
+    /// use in place of parameters, if the challenge has one.
+    pub token68: Option<String>,
+}
+
+impl Challenge {
+    /// Return the value of the first parameter of the challenge with the
+    /// given name, which is matched without regard to case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response
+    ///     .headers
+    ///     .set_header("WWW-Authenticate", "Basic realm=\"Staging\"");
+    /// let challenges = response.www_authenticate();
+    /// assert_eq!(Some("Staging"), challenges[0].parameter("Realm"));
+    /// ```
+    #[must_use]
+    pub fn parameter<T>(
+        &self,
+        name: T,
+    ) -> Option<&str>
+    where
+        T: AsRef<str>,
+    {
+        let name = name.as_ref();
+        self.parameters.iter().find_map(|(parameter_name, value)| {
+            if parameter_name.eq_ignore_ascii_case(name) {
+                Some(value.as_str())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+// Split the given text into its leading token and whatever follows it.
+fn split_token(text: &str) -> (&str, &str) {
+    let token_end = text.find(|c| !is_tchar(c)).unwrap_or(text.len());
+    text.split_at(token_end)
+}
+
+// Parse the given text as an authentication parameter, returning its name and
+// value, or `None` if the text isn't in the form of a parameter.  A token68,
+// which may end in equals signs, isn't taken as a parameter.
+fn parse_parameter(text: &str) -> Option<(String, String)> {
+    let (name, rest) = split_token(text);
+    let value = rest.trim_start().strip_prefix('=')?.trim();
+    if name.is_empty() || value.chars().all(|c| c == '=') {
+        None
+    } else {
+        Some((name.to_string(), unquote(value)))
+    }
+}
+
+// Parse the value of a `WWW-Authenticate` header into the challenges it
+// lists.  Since parameters are also separated by commas, each element of the
+// list either begins a new challenge, if it starts with a scheme, or adds a
+// parameter to the challenge before it.  Malformed elements are skipped.
+pub(crate) fn parse_challenges(list: &str) -> Vec<Challenge> {
+    let mut challenges: Vec<Challenge> = Vec::new();
+    for element in split_quoted(list, ',') {
+        if let Some(parameter) = parse_parameter(element) {
+            if let Some(challenge) = challenges.last_mut() {
+                challenge.parameters.push(parameter);
+            }
+            continue;
+        }
+        let (scheme, rest) = split_token(element);
+        if scheme.is_empty()
+            || !(rest.is_empty() || rest.starts_with([' ', '\t']))
+        {
+            continue;
+        }
+        let rest = rest.trim();
+        let mut challenge = Challenge {
+            parameters: Vec::new(),
+            scheme: scheme.to_string(),
+            token68: None,
+        };
+        if let Some(parameter) = parse_parameter(rest) {
+            challenge.parameters.push(parameter);
+        } else if !rest.is_empty() {
+            challenge.token68 = Some(rest.to_string());
+        }
+        challenges.push(challenge);
+    }
+    challenges
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_multiple_challenges() {
+        assert_eq!(
+            vec![
+                Challenge {
+                    parameters: vec![
+                        ("realm".into(), "Staging, \"main\"".into()),
+                        ("charset".into(), "UTF-8".into()),
+                    ],
+                    scheme: "Basic".into(),
+                    token68: None,
+                },
+                Challenge {
+                    parameters: Vec::new(),
+                    scheme: "Negotiate".into(),
+                    token68: Some("YII=".into()),
+                },
+                Challenge {
+                    parameters: Vec::new(),
+                    scheme: "Custom".into(),
+                    token68: None,
+                },
+            ],
+            parse_challenges(concat!(
+                "Basic realm=\"Staging, \\\"main\\\"\", charset = UTF-8, ",
+                "Negotiate YII=, Custom",
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_challenges_skips_malformed_elements() {
+        assert_eq!(
+            vec![Challenge {
+                parameters: vec![("realm".into(), "x".into())],
+                scheme: "Bearer".into(),
+                token68: None,
+            }],
+            parse_challenges("realm=orphan, \"junk\", Bearer realm=x,,")
+        );
+    }
+}
@@ -1,18 +1,135 @@
 use super::{
     error::Error,
-    find_crlf,
+    is_tchar,
+    LineTerminator,
+    TrailerMergeStrategy,
     CRLF,
+    MAX_BODY_PREALLOCATION,
 };
 use rhymessage::MessageHeaders;
+use std::{
+    iter::Peekable,
+    str::Chars,
+};
 
-fn parse_chunk_size(chunk_size_line: &str) -> Result<usize, Error> {
-    let delimiter = chunk_size_line
-        .find(|c| c == ';' || c == '\r')
-        .unwrap_or_else(|| chunk_size_line.len());
-    let chunk_size = &chunk_size_line[..delimiter];
+// This is the maximum number of bytes of junk to attach to an
+// `Error::InvalidChunkTerminator` error.
+const MAX_JUNK_WINDOW: usize = 16;
+
+// This holds a single chunk extension, as found in a chunk size line.  The
+// value, if any, has had any quoting and escaping removed.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ChunkExtension {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+// A quoted-pair may escape any character other than a control character.
+fn is_escapable(c: char) -> bool {
+    c == '\t' || c == ' ' || c.is_ascii_graphic() || !c.is_ascii()
+}
+
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t')
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|&c| is_whitespace(c)).is_some() {}
+}
+
+fn parse_token(chars: &mut Peekable<Chars>) -> Option<String> {
+    let mut token = String::new();
+    while let Some(c) = chars.next_if(|&c| is_tchar(c)) {
+        token.push(c);
+    }
+    if token.is_empty() {
+        None
+    } else {
+        Some(token)
+    }
+}
+
+// Parse a quoted-string according to the rules of [IETF RFC 7230 section
+// 3.2.6](https://tools.ietf.org/html/rfc7230#section-3.2.6), returning its
+// contents with the quotes removed and any quoted-pairs unescaped.  Since
+// delimiters inside the quotes are just text, a `;` or an escaped `"` in the
+// value doesn't end it.
+fn parse_quoted_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    chars.next_if_eq(&'"')?;
+    let mut value = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(value),
+            '\\' => value.push(chars.next().filter(|&c| is_escapable(c))?),
+            c if is_escapable(c) => value.push(c),
+            _ => return None,
+        }
+    }
+}
+
+// Parse the chunk extensions which follow the chunk size in a chunk size
+// line, according to the rules of [IETF RFC 7230 section
+// 4.1.1](https://tools.ietf.org/html/rfc7230#section-4.1.1), allowing
+// optional whitespace around the delimiters.  `None` is returned if the
+// extensions are not valid.
+fn parse_chunk_extensions(extensions: &str) -> Option<Vec<ChunkExtension>> {
+    let mut chars = extensions.chars().peekable();
+    let mut parsed_extensions = Vec::new();
+    loop {
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => return Some(parsed_extensions),
+            Some(';') => (),
+            Some(_) => return None,
+        }
+        skip_whitespace(&mut chars);
+        let name = parse_token(&mut chars)?;
+        skip_whitespace(&mut chars);
+        let value = if chars.next_if_eq(&'=').is_some() {
+            skip_whitespace(&mut chars);
+            if chars.peek() == Some(&'"') {
+                Some(parse_quoted_string(&mut chars)?)
+            } else {
+                Some(parse_token(&mut chars)?)
+            }
+        } else {
+            None
+        };
+        parsed_extensions.push(ChunkExtension {
+            name,
+            value,
+        });
+    }
+}
+
+// Parse the chunk size, which must consist of hexadecimal digits only.  The
+// digits are checked before converting them, since `from_str_radix` would
+// otherwise accept a leading sign.  Any other character is reported as an
+// invalid digit.
+fn parse_chunk_size(chunk_size: &str) -> Result<usize, Error> {
+    if let Some(invalid) = chunk_size.find(|c: char| !c.is_ascii_hexdigit()) {
+        let invalid_end = invalid
+            + chunk_size[invalid..].chars().next().map_or(0, char::len_utf8);
+        return Err(Error::InvalidChunkSize(
+            usize::from_str_radix(&chunk_size[invalid..invalid_end], 16)
+                .unwrap_err(),
+        ));
+    }
     usize::from_str_radix(chunk_size, 16).map_err(Error::InvalidChunkSize)
 }
 
+fn parse_chunk_size_line(
+    chunk_size_line: &str
+) -> Result<(usize, Vec<ChunkExtension>), Error> {
+    let delimiter = chunk_size_line
+        .find(|c| c == ';' || is_whitespace(c))
+        .unwrap_or(chunk_size_line.len());
+    let chunk_size = parse_chunk_size(&chunk_size_line[..delimiter])?;
+    let extensions = parse_chunk_extensions(&chunk_size_line[delimiter..])
+        .ok_or_else(|| Error::InvalidChunkExtension(chunk_size_line.into()))?;
+    Ok((chunk_size, extensions))
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum DecodeStatus {
     Complete,
@@ -37,6 +154,17 @@ enum ChunkedBodyState {
 pub struct ChunkedBody {
     pub buffer: Vec<u8>,
     chunk_bytes_needed: usize,
+    chunk_count: usize,
+    consumed: usize,
+    // If not None, this is the number of bytes of chunk data which may still
+    // be decoded before decoding pauses, reporting that it's incomplete.
+    pub data_budget: Option<usize>,
+    pub extensions: Vec<Vec<ChunkExtension>>,
+    pub line_terminator: LineTerminator,
+    pub max_chunk_count: Option<usize>,
+    // If not None, this is the maximum number of bytes permitted in the chunk
+    // extensions of each chunk size line, counting from the first semicolon.
+    pub max_chunk_ext_bytes: Option<usize>,
     state: ChunkedBodyState,
     pub trailer: MessageHeaders,
 }
@@ -47,6 +175,38 @@ impl ChunkedBody {
         &self.buffer
     }
 
+    // Check that the chunk extensions in the given chunk size line, which may
+    // be only the part of the line received so far, are within the limit set
+    // for them.  This is done before the line is parsed, so that extensions
+    // which are too long are never stored, and before the line is complete,
+    // so that they're rejected without waiting for the rest of them.
+    fn check_chunk_extensions(
+        &self,
+        chunk_size_line: &[u8],
+    ) -> Result<(), Error> {
+        match (
+            self.max_chunk_ext_bytes,
+            chunk_size_line.iter().position(|&b| b == b';'),
+        ) {
+            (Some(max_chunk_ext_bytes), Some(delimiter))
+                if chunk_size_line.len() - delimiter > max_chunk_ext_bytes =>
+            {
+                Err(Error::ChunkExtensionTooLong)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn count_chunk(&mut self) -> Result<(), Error> {
+        self.chunk_count += 1;
+        match self.max_chunk_count {
+            Some(max_chunk_count) if self.chunk_count > max_chunk_count => {
+                Err(Error::TooManyChunks)
+            },
+            _ => Ok(()),
+        }
+    }
+
     pub fn decode<T>(
         &mut self,
         input: T,
@@ -73,6 +233,7 @@ impl ChunkedBody {
                 },
             };
             total_consumed += consumed;
+            self.consumed += consumed;
             match decode_status {
                 DecodeStatusInternal::CompletePart => (),
                 DecodeStatusInternal::CompleteWhole => {
@@ -81,15 +242,44 @@ impl ChunkedBody {
                 DecodeStatusInternal::Incomplete => {
                     return Ok((DecodeStatus::Incomplete, total_consumed));
                 },
-            };
+            }
+        }
+    }
+
+    // Decode the given input, in the same way as `decode`, except that rather
+    // than collecting the body, each piece of chunk data is encoded again as a
+    // chunk and appended to the given output, so that a chunked body can be
+    // passed along without holding all of it in memory.  Once the body is
+    // complete, the last chunk and the trailer are appended as well.
+    pub fn decode_and_encode<T>(
+        &mut self,
+        input: T,
+        output: &mut Vec<u8>,
+    ) -> Result<(DecodeStatus, usize), Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let (decode_status, consumed) = self.decode(input)?;
+        if !self.buffer.is_empty() {
+            output.append(&mut Self::encode(&self.buffer)?);
+            self.buffer.clear();
         }
+        self.extensions.clear();
+        if decode_status == DecodeStatus::Complete {
+            output.append(&mut Self::finish(&self.trailer)?);
+        }
+        Ok((decode_status, consumed))
     }
 
     fn decode_data(
         &mut self,
         raw_message: &[u8],
     ) -> (DecodeStatusInternal, usize) {
-        let consumed = raw_message.len().min(self.chunk_bytes_needed);
+        let mut consumed = raw_message.len().min(self.chunk_bytes_needed);
+        if let Some(data_budget) = &mut self.data_budget {
+            consumed = consumed.min(*data_budget);
+            *data_budget -= consumed;
+        }
         self.chunk_bytes_needed -= consumed;
         self.buffer.extend(&raw_message[..consumed]);
         if self.chunk_bytes_needed == 0 {
@@ -104,7 +294,11 @@ impl ChunkedBody {
         &mut self,
         raw_message: &[u8],
     ) -> Result<(DecodeStatusInternal, usize), Error> {
-        match find_crlf(raw_message) {
+        let chunk_size_line_end = self.line_terminator.find(raw_message);
+        self.check_chunk_extensions(
+            &raw_message[..chunk_size_line_end.unwrap_or(raw_message.len())],
+        )?;
+        match chunk_size_line_end {
             Some(chunk_size_line_end) => {
                 let chunk_size_line = &raw_message[0..chunk_size_line_end];
                 let chunk_size_line = std::str::from_utf8(chunk_size_line)
@@ -112,10 +306,17 @@ impl ChunkedBody {
                         chunk_size_line: chunk_size_line.to_vec(),
                         source,
                     })?;
-                let consumed = chunk_size_line_end + CRLF.len();
-                self.chunk_bytes_needed = parse_chunk_size(chunk_size_line)?;
-                self.buffer
-                    .reserve(self.buffer.len() + self.chunk_bytes_needed);
+                let consumed = chunk_size_line_end + self.line_terminator.len();
+                let (chunk_size, extensions) =
+                    parse_chunk_size_line(chunk_size_line)?;
+                self.chunk_bytes_needed = chunk_size;
+                self.extensions.push(extensions);
+                if self.chunk_bytes_needed > 0 {
+                    self.count_chunk()?;
+                }
+                self.buffer.reserve(
+                    self.chunk_bytes_needed.min(MAX_BODY_PREALLOCATION),
+                );
                 self.state = match self.chunk_bytes_needed {
                     0 => ChunkedBodyState::Trailer,
                     _ => ChunkedBodyState::ChunkData,
@@ -130,13 +331,18 @@ impl ChunkedBody {
         &mut self,
         raw_message: &[u8],
     ) -> Result<(DecodeStatusInternal, usize), Error> {
-        match raw_message {
-            [] | [b'\r'] => Ok((DecodeStatusInternal::Incomplete, 0)),
-            [b'\r', b'\n', ..] => {
-                self.state = ChunkedBodyState::ChunkSize;
-                Ok((DecodeStatusInternal::CompletePart, 2))
-            },
-            _ => Err(Error::InvalidChunkTerminator(raw_message.to_vec())),
+        let terminator = self.line_terminator.as_bytes();
+        if raw_message.starts_with(terminator) {
+            self.state = ChunkedBodyState::ChunkSize;
+            Ok((DecodeStatusInternal::CompletePart, terminator.len()))
+        } else if terminator.starts_with(raw_message) {
+            Ok((DecodeStatusInternal::Incomplete, 0))
+        } else {
+            Err(Error::InvalidChunkTerminator {
+                junk: raw_message[..raw_message.len().min(MAX_JUNK_WINDOW)]
+                    .to_vec(),
+                offset: self.consumed,
+            })
         }
     }
 
@@ -144,8 +350,21 @@ impl ChunkedBody {
         &mut self,
         raw_message: &[u8],
     ) -> Result<(DecodeStatusInternal, usize), Error> {
-        let parse_results =
-            self.trailer.parse(raw_message).map_err(Error::Trailer)?;
+        // With no trailer headers, the empty line which ends the body comes
+        // right after the last chunk, so complete without involving the
+        // header parser.
+        if self.trailer.headers().is_empty()
+            && raw_message.starts_with(self.line_terminator.as_bytes())
+        {
+            return Ok((
+                DecodeStatusInternal::CompleteWhole,
+                self.line_terminator.len(),
+            ));
+        }
+        let parse_results = self
+            .line_terminator
+            .parse_headers(&mut self.trailer, raw_message)
+            .map_err(Error::Trailer)?;
         match parse_results.status {
             rhymessage::ParseStatus::Complete => Ok((
                 DecodeStatusInternal::CompleteWhole,
@@ -157,10 +376,72 @@ impl ChunkedBody {
         }
     }
 
+    // Encode the given bytes as a single chunk of a chunked body, according
+    // to the rules of [IETF RFC 7230 section
+    // 4.1](https://tools.ietf.org/html/rfc7230#section-4.1).  The chunk must
+    // not be empty, since an empty chunk is the last chunk, which is only
+    // written by `finish`.
+    pub fn encode<T>(chunk: T) -> Result<Vec<u8>, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let chunk = chunk.as_ref();
+        if chunk.is_empty() {
+            return Err(Error::EmptyChunk);
+        }
+        let mut output = format!("{:X}{}", chunk.len(), CRLF).into_bytes();
+        output.extend(chunk);
+        output.extend(CRLF.as_bytes());
+        Ok(output)
+    }
+
+    // Produce the last chunk and the given trailer which together terminate
+    // a chunked body.  This is the only way the terminator is written.
+    pub fn finish(trailer: &MessageHeaders) -> Result<Vec<u8>, Error> {
+        let mut output = format!("0{CRLF}").into_bytes();
+        output.append(&mut trailer.generate().map_err(Error::Trailer)?);
+        Ok(output)
+    }
+
+    // Take the decoded body, moving any headers from the trailer into the
+    // given message headers according to the given strategy.  Since the body
+    // is no longer chunked, the "chunked" token is removed from the
+    // `Transfer-Encoding` header, the `Trailer` header is removed, and a
+    // `Content-Length` header is added.
+    pub fn finish_decoding(
+        self,
+        headers: &mut MessageHeaders,
+        trailer_merge_strategy: TrailerMergeStrategy,
+    ) -> Vec<u8> {
+        trailer_merge_strategy.merge(headers, self.trailer);
+        let mut transfer_encodings = headers.header_tokens("Transfer-Encoding");
+        transfer_encodings.pop();
+        if transfer_encodings.is_empty() {
+            headers.remove_header("Transfer-Encoding");
+        } else {
+            headers
+                .set_header("Transfer-Encoding", transfer_encodings.join(" "));
+        }
+        headers.set_header("Content-Length", self.buffer.len().to_string());
+        headers.remove_header("Trailer");
+        self.buffer
+    }
+
+    pub fn is_decoding_trailer(&self) -> bool {
+        self.state == ChunkedBodyState::Trailer
+    }
+
     pub fn new() -> Self {
         Self {
             buffer: Vec::new(),
             chunk_bytes_needed: 0,
+            chunk_count: 0,
+            consumed: 0,
+            data_budget: None,
+            extensions: Vec::new(),
+            line_terminator: LineTerminator::default(),
+            max_chunk_count: None,
+            max_chunk_ext_bytes: None,
             state: ChunkedBodyState::ChunkSize,
             trailer: MessageHeaders::new(),
         }
@@ -172,6 +453,58 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn encode_chunk() {
+        assert!(matches!(
+            ChunkedBody::encode("Hello"),
+            Ok(chunk) if chunk == b"5\r\nHello\r\n"
+        ));
+        assert!(matches!(
+            ChunkedBody::encode("abcdefghijklmnopqrstuvwxyz"),
+            Ok(chunk) if chunk == b"1A\r\nabcdefghijklmnopqrstuvwxyz\r\n"
+        ));
+    }
+
+    #[test]
+    fn encode_empty_chunk() {
+        assert!(matches!(ChunkedBody::encode(""), Err(Error::EmptyChunk)));
+        assert!(matches!(
+            ChunkedBody::encode(Vec::new()),
+            Err(Error::EmptyChunk)
+        ));
+    }
+
+    #[test]
+    fn finish_without_trailer() {
+        assert!(matches!(
+            ChunkedBody::finish(&MessageHeaders::new()),
+            Ok(output) if output == b"0\r\n\r\n"
+        ));
+    }
+
+    #[test]
+    fn finish_with_trailer() {
+        let mut trailer = MessageHeaders::new();
+        trailer.set_header("X-Foo", "Bar");
+        assert!(matches!(
+            ChunkedBody::finish(&trailer),
+            Ok(output) if output == b"0\r\nX-Foo: Bar\r\n\r\n"
+        ));
+    }
+
+    #[test]
+    fn encode_then_decode() {
+        let mut input = ChunkedBody::encode("Hello, ").unwrap();
+        input.extend(ChunkedBody::encode("World!").unwrap());
+        input.extend(ChunkedBody::finish(&MessageHeaders::new()).unwrap());
+        let mut body = ChunkedBody::new();
+        assert!(matches!(
+            body.decode(&input),
+            Ok((DecodeStatus::Complete, consumed)) if consumed == input.len()
+        ));
+        assert_eq!(b"Hello, World!", body.as_bytes());
+    }
+
     #[test]
     fn decode_simple_empty_body_one_piece() {
         let input = "0\r\n\r\n";
@@ -238,6 +571,107 @@ mod tests {
         assert_eq!(b"", body.as_bytes());
     }
 
+    #[test]
+    fn decode_chunk_extensions_are_recorded() {
+        let input =
+            "5;Foo=Bar;Kappa=\"Hello, World!\";Spam\r\nHello\r\n0\r\n\r\n";
+        let mut body = ChunkedBody::new();
+        assert!(matches!(
+            body.decode(input),
+            Ok((DecodeStatus::Complete, consumed)) if consumed == input.len()
+        ));
+        assert_eq!(b"Hello", body.as_bytes());
+        assert_eq!(
+            vec![
+                vec![
+                    ChunkExtension {
+                        name: "Foo".into(),
+                        value: Some("Bar".into()),
+                    },
+                    ChunkExtension {
+                        name: "Kappa".into(),
+                        value: Some("Hello, World!".into()),
+                    },
+                    ChunkExtension {
+                        name: "Spam".into(),
+                        value: None,
+                    },
+                ],
+                vec![],
+            ],
+            body.extensions
+        );
+    }
+
+    #[test]
+    fn decode_chunk_extension_with_escaped_quote() {
+        let input = "0;a=\"a\\\"b\";c=d\r\n\r\n";
+        let mut body = ChunkedBody::new();
+        assert!(matches!(
+            body.decode(input),
+            Ok((DecodeStatus::Complete, consumed)) if consumed == input.len()
+        ));
+        assert_eq!(
+            vec![vec![
+                ChunkExtension {
+                    name: "a".into(),
+                    value: Some("a\"b".into()),
+                },
+                ChunkExtension {
+                    name: "c".into(),
+                    value: Some("d".into()),
+                },
+            ]],
+            body.extensions
+        );
+    }
+
+    #[test]
+    fn decode_chunk_extension_with_embedded_semicolon() {
+        let input = "0 ; a = \"x;y\" ; b=\"\\\\\"\r\n\r\n";
+        let mut body = ChunkedBody::new();
+        assert!(matches!(
+            body.decode(input),
+            Ok((DecodeStatus::Complete, consumed)) if consumed == input.len()
+        ));
+        assert_eq!(
+            vec![vec![
+                ChunkExtension {
+                    name: "a".into(),
+                    value: Some("x;y".into()),
+                },
+                ChunkExtension {
+                    name: "b".into(),
+                    value: Some("\\".into()),
+                },
+            ]],
+            body.extensions
+        );
+    }
+
+    #[test]
+    fn decode_invalid_chunk_extensions() {
+        for input in &[
+            "0;a=\"unterminated\r\n\r\n",
+            "0;a=\"bad\\\x01escape\"\r\n\r\n",
+            "0;=value\r\n\r\n",
+            "0;a=\r\n\r\n",
+            "0;a=b c\r\n\r\n",
+            "0;a=\"b\"c\r\n\r\n",
+        ] {
+            let mut body = ChunkedBody::new();
+            assert!(
+                matches!(
+                    body.decode(input),
+                    Err(Error::InvalidChunkExtension(line))
+                        if line == input[..input.len() - 4]
+                ),
+                "{:?}",
+                input
+            );
+        }
+    }
+
     #[test]
     fn decode_simple_empty_body_one_character_at_a_time() {
         let input = b"0\r\n\r\n";
@@ -248,22 +682,17 @@ mod tests {
             accepted += consumed;
             match i {
                 0..=1 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkSize,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkSize, body.state, "{i}");
                     assert_eq!(0, accepted);
                 },
                 2..=3 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{i}");
                     assert_eq!(3, accepted);
                 },
                 _ => {
-                    assert_eq!(DecodeStatus::Complete, status, "{}", i);
+                    assert_eq!(DecodeStatus::Complete, status, "{i}");
                     assert_eq!(5, accepted);
                 },
             }
@@ -316,52 +745,36 @@ mod tests {
             accepted += consumed;
             match i {
                 0..=1 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkSize,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkSize, body.state, "{i}");
                     assert_eq!(0, accepted);
                 },
                 2..=6 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkData,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkData, body.state, "{i}");
                     assert_eq!(i + 1, accepted);
                 },
                 7..=8 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
                     assert_eq!(
                         ChunkedBodyState::ChunkTerminator,
                         body.state,
-                        "{}",
-                        i
+                        "{i}"
                     );
                     assert_eq!(8, accepted);
                 },
                 9..=11 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkSize,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkSize, body.state, "{i}");
                     assert_eq!(10, accepted);
                 },
                 12..=13 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{i}");
                     assert_eq!(13, accepted);
                 },
                 _ => {
-                    assert_eq!(DecodeStatus::Complete, status, "{}", i);
+                    assert_eq!(DecodeStatus::Complete, status, "{i}");
                     assert_eq!(15, accepted);
                 },
             }
@@ -394,82 +807,55 @@ mod tests {
             #[allow(clippy::match_same_arms)]
             match i {
                 0..=1 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkSize,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkSize, body.state, "{i}");
                     assert_eq!(0, accepted);
                 },
                 2..=7 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkData,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkData, body.state, "{i}");
                     assert_eq!(i + 1, accepted);
                 },
                 8..=9 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
                     assert_eq!(
                         ChunkedBodyState::ChunkTerminator,
                         body.state,
-                        "{}",
-                        i
+                        "{i}"
                     );
                     assert_eq!(9, accepted);
                 },
                 10..=12 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkSize,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkSize, body.state, "{i}");
                     assert_eq!(11, accepted);
                 },
                 13..=19 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkData,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkData, body.state, "{i}");
                     assert_eq!(i + 1, accepted);
                 },
                 20..=21 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
                     assert_eq!(
                         ChunkedBodyState::ChunkTerminator,
                         body.state,
-                        "{}",
-                        i
+                        "{i}"
                     );
                     assert_eq!(21, accepted);
                 },
                 22..=24 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkSize,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkSize, body.state, "{i}");
                     assert_eq!(23, accepted);
                 },
                 25..=26 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{i}");
                     assert_eq!(26, accepted);
                 },
                 _ => {
-                    assert_eq!(DecodeStatus::Complete, status, "{}", i);
+                    assert_eq!(DecodeStatus::Complete, status, "{i}");
                     assert_eq!(28, accepted);
                 },
             }
@@ -477,6 +863,78 @@ mod tests {
         assert_eq!(b"Hello, World!", body.as_bytes());
     }
 
+    #[test]
+    fn decode_pauses_when_data_budget_is_spent() {
+        let input = "5\r\nHello\r\n3\r\nabc\r\n0\r\n\r\n";
+        let mut body = ChunkedBody::new();
+        body.data_budget = Some(7);
+        assert!(matches!(
+            body.decode(input),
+            Ok((DecodeStatus::Incomplete, 15))
+        ));
+        assert_eq!(b"Helloab", body.as_bytes());
+        assert_eq!(Some(0), body.data_budget);
+        body.data_budget = None;
+        assert!(matches!(
+            body.decode(&input[15..]),
+            Ok((DecodeStatus::Complete, 8))
+        ));
+        assert_eq!(b"Helloabc", body.as_bytes());
+    }
+
+    #[test]
+    fn decode_and_encode_passes_chunks_through() {
+        let input = concat!(
+            "5\r\nHello\r\n",
+            "6\r\nWorld!\r\n",
+            "0\r\n",
+            "X-Foo: Bar\r\n",
+            "\r\n",
+        );
+        let mut body = ChunkedBody::new();
+        let mut output = Vec::new();
+        assert!(matches!(
+            body.decode_and_encode(&input[..15], &mut output),
+            Ok((DecodeStatus::Incomplete, 15))
+        ));
+        assert_eq!(&b"7\r\nHelloWo\r\n"[..], output);
+        assert!(body.as_bytes().is_empty());
+        assert!(matches!(
+            body.decode_and_encode(&input[15..], &mut output),
+            Ok((DecodeStatus::Complete, consumed))
+                if consumed == input.len() - 15
+        ));
+        assert_eq!(
+            concat!(
+                "7\r\nHelloWo\r\n",
+                "4\r\nrld!\r\n",
+                "0\r\n",
+                "X-Foo: Bar\r\n",
+                "\r\n",
+            )
+            .as_bytes(),
+            output
+        );
+    }
+
+    #[test]
+    fn decode_without_trailers() {
+        let input = "5\r\nHello\r\n0\r\n\r\nHTTP/1.1";
+        let mut body = ChunkedBody::new();
+        assert!(matches!(
+            body.decode(input),
+            Ok((DecodeStatus::Complete, consumed)) if consumed == input.len() - 8
+        ));
+        assert_eq!(b"Hello", body.as_bytes());
+        assert!(body.trailer.headers().is_empty());
+        let mut body = ChunkedBody::new();
+        assert!(matches!(
+            body.decode("0\r\n\r"),
+            Ok((DecodeStatus::Incomplete, 3))
+        ));
+        assert!(matches!(body.decode("\r\n"), Ok((DecodeStatus::Complete, 2))));
+    }
+
     #[test]
     fn decode_trailers_one_piece() {
         let input = "0\r\nX-Foo: Bar\r\nX-Poggers: FeelsBadMan\r\n\r\n";
@@ -515,27 +973,22 @@ mod tests {
             #[allow(clippy::match_same_arms)]
             match i {
                 0..=1 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(
-                        ChunkedBodyState::ChunkSize,
-                        body.state,
-                        "{}",
-                        i
-                    );
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::ChunkSize, body.state, "{i}");
                     assert_eq!(0, accepted);
                 },
                 2..=37 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{i}");
                     assert_eq!(3, accepted);
                 },
                 38..=39 => {
-                    assert_eq!(DecodeStatus::Incomplete, status, "{}", i);
-                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{}", i);
+                    assert_eq!(DecodeStatus::Incomplete, status, "{i}");
+                    assert_eq!(ChunkedBodyState::Trailer, body.state, "{i}");
                     assert_eq!(15, accepted);
                 },
                 _ => {
-                    assert_eq!(DecodeStatus::Complete, status, "{}", i);
+                    assert_eq!(DecodeStatus::Complete, status, "{i}");
                     assert_eq!(41, accepted);
                 },
             }
@@ -563,6 +1016,18 @@ mod tests {
         assert!(matches!(body.decode(input), Err(Error::InvalidChunkSize(_))));
     }
 
+    #[test]
+    fn decode_bad_chunk_size_line_not_only_hexdig_in_chunk_size() {
+        for input in &[" 1a\r\n", "0x1a\r\n", "-1\r\n", "+1a\r\n"] {
+            let mut body = ChunkedBody::new();
+            assert!(
+                matches!(body.decode(input), Err(Error::InvalidChunkSize(_))),
+                "{:?}",
+                input
+            );
+        }
+    }
+
     #[test]
     fn decode_bad_chunk_size_line_chunk_size_overflow() {
         let input = "111111111111111111111111111111111111111111111111111111111111111\r\n\r\n";
@@ -579,8 +1044,44 @@ mod tests {
         let mut body = ChunkedBody::new();
         assert!(matches!(
             body.decode(input),
-            Err(Error::InvalidChunkTerminator(junk)) if junk == b"junk\r\n"
+            Err(Error::InvalidChunkTerminator{
+                junk,
+                offset: 4
+            }) if junk == b"junk\r\n"
+        ));
+    }
+
+    #[test]
+    fn decode_bad_junk_after_chunk_truncated() {
+        let input = "1\r\nXThis is a whole lot of junk after the chunk\r\n";
+        let mut body = ChunkedBody::new();
+        assert!(matches!(
+            body.decode(input),
+            Err(Error::InvalidChunkTerminator{
+                junk,
+                offset: 4
+            }) if junk == b"This is a whole "
+        ));
+    }
+
+    #[test]
+    fn decode_chunk_count_at_limit() {
+        let input = "1\r\nX\r\n1\r\nY\r\n0\r\n\r\n";
+        let mut body = ChunkedBody::new();
+        body.max_chunk_count = Some(2);
+        assert!(matches!(
+            body.decode(input),
+            Ok((DecodeStatus::Complete, consumed)) if consumed == input.len()
         ));
+        assert_eq!(b"XY", body.as_bytes());
+    }
+
+    #[test]
+    fn decode_bad_too_many_chunks() {
+        let input = "1\r\nX\r\n1\r\nY\r\n1\r\nZ\r\n0\r\n\r\n";
+        let mut body = ChunkedBody::new();
+        body.max_chunk_count = Some(2);
+        assert!(matches!(body.decode(input), Err(Error::TooManyChunks)));
     }
 
     #[test]
@@ -6,31 +6,100 @@
 //! of the body are interpreted as text, and therefore how it can be
 //! converted to/from a Rust string.
 
-use crate::Error;
+use crate::{
+    split_quoted,
+    Error,
+    MediaType,
+};
 use flate2::bufread::{
     DeflateDecoder,
-    GzDecoder,
+    MultiGzDecoder,
 };
 use rhymessage::MessageHeaders;
-use std::io::Read as _;
+use std::io::Read;
+
+/// This identifies which of the headers of a message lists a coding applied
+/// to its body.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CodingLayer {
+    /// The coding is listed in the `Content-Encoding` header, and is a
+    /// property of the representation itself.
+    Content,
+
+    /// The coding is listed in the `Transfer-Encoding` header, and was
+    /// applied only to transfer the message.
+    Transfer,
+}
+
+/// This describes one of the codings applied to the body of a message, as
+/// listed by [`Response::body_codings`].
+///
+/// [`Response::body_codings`]: ../struct.Response.html#method.body_codings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Coding {
+    /// This identifies the header which lists the coding.
+    pub layer: CodingLayer,
+
+    /// This is the name of the coding, normalized to lowercase, such as
+    /// `gzip`.
+    pub name: String,
+
+    /// This indicates whether or not this crate can reverse the coding:
+    /// the "chunked" transfer coding is removed by the parser, and the
+    /// `gzip` and `deflate` content codings by [`decode_body`].
+    pub supported: bool,
+}
+
+// List the codings in all `Transfer-Encoding` headers, followed by those in
+// all `Content-Encoding` headers, each in the order in which they're listed.
+// The `identity` coding is left out, since it doesn't change the body.
+pub(crate) fn body_codings(headers: &MessageHeaders) -> Vec<Coding> {
+    let transfer_codings = headers
+        .header_tokens("Transfer-Encoding")
+        .into_iter()
+        .filter(|coding| !coding.is_empty() && coding != "identity")
+        .map(|name| Coding {
+            layer: CodingLayer::Transfer,
+            supported: name == "chunked",
+            name,
+        });
+    let content_codings =
+        content_codings(headers).into_iter().map(|name| Coding {
+            layer: CodingLayer::Content,
+            supported: matches!(name.as_str(), "gzip" | "deflate"),
+            name,
+        });
+    transfer_codings.chain(content_codings).collect()
+}
 
 /// Attempt to reverse any content coding that has been performed on the given
 /// message body, as indicated in the given message headers.  The content
 /// codings that were performed on the body are listed in the
 /// `Content-Encoding` header, in the same order that the encoding was
 /// performed.  Therefore, to decode the body, the decoding is performed in
-/// reverse order.  Decoding is stopped if any unrecognized coding is
+/// reverse order.  The `identity` coding is removed without modifying the
+/// body.  Decoding is stopped if any unrecognized coding is
 /// encountered, or any error occurs during the decoding process.  Any codings
 /// successfully decoded are removed from the `Content-Encoding` header, and
 /// the header itself is removed if all codings are decoded.
 ///
+/// If `max_decoded_size` is given, no decoding step is allowed to produce
+/// more than that many bytes.  Decompression is stopped as soon as the limit
+/// is exceeded, so that a small body can't be expanded to exhaust memory.
+///
 /// # Errors
 ///
-/// [`Error::BadContentEncoding`](../enum.Error.html#variant.BadContentEncoding)
-/// is returned if an error occurs during the decoding process.
+/// * [`Error::BadContentEncoding`][BadContentEncoding] is returned if an error
+///   occurs during the decoding process.
+/// * [`Error::DecodedBodyTooLarge`][DecodedBodyTooLarge] is returned if the
+///   decoded body would be larger than `max_decoded_size`.
+///
+/// [BadContentEncoding]: ../enum.Error.html#variant.BadContentEncoding
+/// [DecodedBodyTooLarge]: ../enum.Error.html#variant.DecodedBodyTooLarge
 pub fn decode_body<B>(
     headers: &mut MessageHeaders,
     body: B,
+    max_decoded_size: Option<usize>,
 ) -> Result<Vec<u8>, Error>
 where
     B: AsRef<[u8]>,
@@ -39,13 +108,14 @@ where
     let mut body = body.as_ref().to_vec();
     while let Some(coding) = codings.pop() {
         match coding.as_ref() {
-            "gzip" => body = gzip_decode(body)?,
-            "deflate" => body = deflate_decode(body)?,
+            "gzip" => body = gzip_decode(body, max_decoded_size)?,
+            "deflate" => body = deflate_decode(body, max_decoded_size)?,
+            "identity" => (),
             _ => {
                 codings.push(coding);
                 break;
             },
-        };
+        }
     }
     if codings.is_empty() {
         headers.remove_header("Content-Encoding");
@@ -62,6 +132,10 @@ where
 /// `charset` is missing) is a text encoding recognized and supported by the
 /// [`encoding_rs`](https://crates.io/crates/encoding_rs) crate, and the text
 /// is successfully decoded.
+///
+/// Any byte order mark (BOM) at the start of the body is treated as part of
+/// the text.  Use [`decode_body_as_text_with_bom_handling`] to detect and
+/// strip it instead.
 #[must_use]
 pub fn decode_body_as_text<B>(
     headers: &MessageHeaders,
@@ -70,63 +144,136 @@ pub fn decode_body_as_text<B>(
 where
     B: AsRef<[u8]>,
 {
-    if let Some(content_type) = headers.header_value("Content-Type") {
-        let (type_subtype, parameters) = match content_type.find(';') {
-            Some(delimiter) => {
-                (&content_type[..delimiter], &content_type[delimiter + 1..])
+    text_encoding(headers).and_then(|encoding| {
+        encoding
+            .decode_without_bom_handling_and_without_replacement(body.as_ref())
+            .map(String::from)
+    })
+}
+
+/// Attempt to decode the given message body as text, in the same way as
+/// [`decode_body_as_text`], except that a UTF-8, UTF-16LE, or UTF-16BE byte
+/// order mark (BOM) at the start of the body is detected and stripped.  If
+/// there is a BOM, the encoding it indicates takes precedence over the
+/// `charset` parameter of the `Content-Type` header.
+#[must_use]
+pub fn decode_body_as_text_with_bom_handling<B>(
+    headers: &MessageHeaders,
+    body: B,
+) -> Option<String>
+where
+    B: AsRef<[u8]>,
+{
+    let body = body.as_ref();
+    text_encoding(headers).and_then(|encoding| {
+        let (encoding, body) = match encoding_rs::Encoding::for_bom(body) {
+            Some((bom_encoding, bom_length)) => {
+                (bom_encoding, &body[bom_length..])
             },
-            None => (&content_type[..], ""),
+            None => (encoding, body),
         };
-        if let Some((r#type, _)) = split_at(type_subtype, '/') {
-            if !r#type.eq_ignore_ascii_case("text") {
-                return None;
-            }
-            let charset = parameters
-                .split(';')
-                .map(str::trim)
-                .filter_map(|parameter| split_at(parameter, '='))
-                .find_map(|(name, value)| {
-                    if name.eq_ignore_ascii_case("charset") {
-                        Some(value)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or("iso-8859-1");
-            if let Some(encoding) =
-                encoding_rs::Encoding::for_label(charset.as_bytes())
-            {
-                return encoding
-                    .decode_without_bom_handling_and_without_replacement(
-                        body.as_ref(),
-                    )
-                    .map(String::from);
-            }
-        }
-    }
-    None
+        encoding
+            .decode_without_bom_handling_and_without_replacement(body)
+            .map(String::from)
+    })
+}
+
+// List the content codings in all `Content-Encoding` headers, normalized to
+// lowercase, other than `identity`, which doesn't change the body.
+fn content_codings(headers: &MessageHeaders) -> Vec<String> {
+    headers
+        .header_tokens("Content-Encoding")
+        .into_iter()
+        .filter(|coding| !coding.is_empty() && coding != "identity")
+        .collect()
+}
+
+// Determine whether or not any content coding has been performed on the body
+// of the message with the given headers.
+pub(crate) fn is_body_encoded(headers: &MessageHeaders) -> bool {
+    !content_codings(headers).is_empty()
 }
 
-fn deflate_decode<B>(body: B) -> Result<Vec<u8>, Error>
+// Determine whether or not every content coding performed on the body of the
+// message with the given headers is one which `decode_body` can reverse.
+pub(crate) fn has_supported_encoding(headers: &MessageHeaders) -> bool {
+    content_codings(headers)
+        .iter()
+        .all(|coding| matches!(coding.as_str(), "gzip" | "deflate"))
+}
+
+fn deflate_decode<B>(
+    body: B,
+    max_decoded_size: Option<usize>,
+) -> Result<Vec<u8>, Error>
 where
     B: AsRef<[u8]>,
 {
-    let body = body.as_ref();
-    let mut decoder = DeflateDecoder::new(body);
-    let mut body = Vec::new();
-    decoder.read_to_end(&mut body).map_err(Error::BadContentEncoding)?;
-    Ok(body)
+    read_decoded(DeflateDecoder::new(body.as_ref()), max_decoded_size)
 }
 
-fn gzip_decode<B>(body: B) -> Result<Vec<u8>, Error>
+// Decode a gzip body.  The body may consist of more than one gzip member,
+// one after the other, as described in [IETF RFC 1952 section
+// 2.2](https://tools.ietf.org/html/rfc1952#section-2.2), so all of the
+// members are decoded, rather than just the first one.
+fn gzip_decode<B>(
+    body: B,
+    max_decoded_size: Option<usize>,
+) -> Result<Vec<u8>, Error>
 where
     B: AsRef<[u8]>,
 {
-    let body = body.as_ref();
-    let mut decoder = GzDecoder::new(body);
+    read_decoded(MultiGzDecoder::new(body.as_ref()), max_decoded_size)
+}
+
+// Read all the bytes produced by the given decoder, stopping early with
+// `Error::DecodedBodyTooLarge` if it produces more than `max_decoded_size`
+// bytes.  One byte past the limit is read so that a body of exactly the
+// maximum size is accepted.
+fn read_decoded<R>(
+    decoder: R,
+    max_decoded_size: Option<usize>,
+) -> Result<Vec<u8>, Error>
+where
+    R: Read,
+{
+    let limit = max_decoded_size.map_or(u64::MAX, |max_decoded_size| {
+        (max_decoded_size as u64).saturating_add(1)
+    });
     let mut body = Vec::new();
-    decoder.read_to_end(&mut body).map_err(Error::BadContentEncoding)?;
-    Ok(body)
+    decoder
+        .take(limit)
+        .read_to_end(&mut body)
+        .map_err(Error::BadContentEncoding)?;
+    match max_decoded_size {
+        Some(max_decoded_size) if body.len() > max_decoded_size => {
+            Err(Error::DecodedBodyTooLarge)
+        },
+        _ => Ok(body),
+    }
+}
+
+/// Parse the `Content-Type` header in the given message headers into its
+/// type, subtype, and parameters.  `None` is returned if there is no
+/// `Content-Type` header, or its value is not a valid media type.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate rhymuweb;
+/// use rhymessage::MessageHeaders;
+/// use rhymuweb::coding::parse_content_type;
+///
+/// let mut headers = MessageHeaders::new();
+/// headers.set_header("Content-Type", "text/html; charset=utf-8");
+/// let content_type = parse_content_type(&headers).unwrap();
+/// assert_eq!("text", content_type.r#type);
+/// assert_eq!("html", content_type.subtype);
+/// assert_eq!(Some("utf-8"), content_type.parameter("charset"));
+/// ```
+#[must_use]
+pub fn parse_content_type(headers: &MessageHeaders) -> Option<MediaType> {
+    headers.header_value("Content-Type").and_then(MediaType::parse)
 }
 
 fn split_at(
@@ -141,6 +288,51 @@ fn split_at(
     }
 }
 
+// This is a media type split into its type, subtype, and parameters.
+type MediaTypeParts<'a> = (&'a str, &'a str, Vec<(&'a str, &'a str)>);
+
+// Split a media type, such as the value of a `Content-Type` header or one
+// element of an `Accept` header, into its type, subtype, and parameters.  The
+// parameters are returned as name/value pairs, in order, with any whitespace
+// around them removed.  `None` is returned if there is no subtype.
+pub(crate) fn split_media_type(media_type: &str) -> Option<MediaTypeParts<'_>> {
+    let (type_subtype, parameters) = match media_type.find(';') {
+        Some(delimiter) => {
+            (&media_type[..delimiter], &media_type[delimiter + 1..])
+        },
+        None => (media_type, ""),
+    };
+    let (r#type, subtype) = split_at(type_subtype, '/')?;
+    Some((r#type.trim(), subtype.trim(), split_parameters(parameters)))
+}
+
+// Split the parameters which follow a value such as a media type, separated
+// by semicolons, into name/value pairs, in order, with any whitespace around
+// them removed.  Semicolons within quoted values don't separate parameters.
+// Parameters without an equals sign are skipped.
+pub(crate) fn split_parameters(parameters: &str) -> Vec<(&str, &str)> {
+    split_quoted(parameters, ';')
+        .into_iter()
+        .filter_map(|parameter| split_at(parameter, '='))
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .collect()
+}
+
+// Determine the text encoding of a message body from the `Content-Type`
+// header in the given message headers.  The type must be `text`, and the
+// `charset` parameter, which defaults to `iso-8859-1`, must name an encoding
+// supported by `encoding_rs`.
+fn text_encoding(
+    headers: &MessageHeaders
+) -> Option<&'static encoding_rs::Encoding> {
+    let content_type = parse_content_type(headers)?;
+    if !content_type.r#type.eq_ignore_ascii_case("text") {
+        return None;
+    }
+    let charset = content_type.parameter("charset").unwrap_or("iso-8859-1");
+    encoding_rs::Encoding::for_label(charset.as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -149,6 +341,39 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn body_codings_lists_transfer_then_content_codings() {
+        let mut headers = MessageHeaders::new();
+        headers.set_header("Content-Encoding", "deflate, identity, br");
+        headers.set_header("Transfer-Encoding", "gzip, Chunked");
+        assert_eq!(
+            vec![
+                Coding {
+                    layer: CodingLayer::Transfer,
+                    name: "gzip".into(),
+                    supported: false,
+                },
+                Coding {
+                    layer: CodingLayer::Transfer,
+                    name: "chunked".into(),
+                    supported: true,
+                },
+                Coding {
+                    layer: CodingLayer::Content,
+                    name: "deflate".into(),
+                    supported: true,
+                },
+                Coding {
+                    layer: CodingLayer::Content,
+                    name: "br".into(),
+                    supported: false,
+                },
+            ],
+            body_codings(&headers)
+        );
+        assert!(body_codings(&MessageHeaders::new()).is_empty());
+    }
+
     #[test]
     fn gzip_decode_non_empty_input() {
         let body: &[u8] = &[
@@ -156,23 +381,37 @@ mod tests {
             0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0x08, 0xCF, 0x2F, 0xCA, 0x49,
             0x51, 0x04, 0x00, 0xD0, 0xC3, 0x4A, 0xEC, 0x0D, 0x00, 0x00, 0x00,
         ];
-        let body = gzip_decode(body);
+        let body = gzip_decode(body, None);
         assert!(body.is_ok());
         let body = body.unwrap();
         assert_eq!("Hello, World!".as_bytes(), body);
     }
 
+    #[test]
+    fn gzip_decode_concatenated_members() {
+        let member: &[u8] = &[
+            0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0A, 0xF3,
+            0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0x08, 0xCF, 0x2F, 0xCA, 0x49,
+            0x51, 0x04, 0x00, 0xD0, 0xC3, 0x4A, 0xEC, 0x0D, 0x00, 0x00, 0x00,
+        ];
+        let body = [member, member].concat();
+        let body = gzip_decode(body, None);
+        assert!(body.is_ok());
+        let body = body.unwrap();
+        assert_eq!("Hello, World!Hello, World!".as_bytes(), body);
+    }
+
     #[test]
     fn gzip_decode_empty_input() {
         let body: &[u8] = &[];
-        let body = gzip_decode(body);
+        let body = gzip_decode(body, None);
         assert!(matches!(body, Err(Error::BadContentEncoding(_))));
     }
 
     #[test]
     fn gzip_decode_junk() {
         let body: &[u8] = b"Hello, this is certainly not gzipped data!";
-        let body = gzip_decode(body);
+        let body = gzip_decode(body, None);
         assert!(matches!(body, Err(Error::BadContentEncoding(_))));
     }
 
@@ -183,7 +422,7 @@ mod tests {
             0x65, 0x73, 0x74, 0x2e, 0x74, 0x78, 0x74, 0x00, 0x03, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
-        let body = gzip_decode(body);
+        let body = gzip_decode(body, None);
         assert!(body.is_ok());
         let body = body.unwrap();
         assert_eq!("".as_bytes(), body);
@@ -195,7 +434,7 @@ mod tests {
             0xf3, 0x48, 0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0x08, 0xcf, 0x2f, 0xca,
             0x49, 0x51, 0x04, 0x00,
         ];
-        let body = deflate_decode(body);
+        let body = deflate_decode(body, None);
         assert!(body.is_ok());
         let body = body.unwrap();
         assert_eq!("Hello, World!".as_bytes(), body);
@@ -204,21 +443,21 @@ mod tests {
     #[test]
     fn deflate_decode_empty_input() {
         let body: &[u8] = &[];
-        let body = deflate_decode(body);
+        let body = deflate_decode(body, None);
         assert!(matches!(body, Err(Error::BadContentEncoding(_))));
     }
 
     #[test]
     fn deflate_decode_junk() {
         let body: &[u8] = b"Hello, this is certainly not deflated data!";
-        let body = deflate_decode(body);
+        let body = deflate_decode(body, None);
         assert!(matches!(body, Err(Error::BadContentEncoding(_))));
     }
 
     #[test]
     fn deflate_decode_empty_output() {
         let body: &[u8] = &[0x03, 0x00];
-        let body = deflate_decode(body);
+        let body = deflate_decode(body, None);
         assert!(body.is_ok());
         let body = body.unwrap();
         assert_eq!("".as_bytes(), body);
@@ -230,11 +469,44 @@ mod tests {
         let body = b"Hello, World!";
         headers.set_header("Content-Length", body.len().to_string());
         assert!(matches!(
-            decode_body(&mut headers, body),
+            decode_body(&mut headers, body, None),
             Ok(body) if body == b"Hello, World!"
         ));
     }
 
+    #[test]
+    fn parse_content_type_from_headers() {
+        let mut headers = MessageHeaders::new();
+        assert_eq!(None, parse_content_type(&headers));
+        headers.set_header("Content-Type", "application/json; charset=utf-8");
+        assert_eq!(
+            Some(MediaType {
+                parameters: vec![("charset".into(), "utf-8".into())],
+                subtype: "json".into(),
+                r#type: "application".into(),
+            }),
+            parse_content_type(&headers)
+        );
+        headers.set_header("Content-Type", "garbage");
+        assert_eq!(None, parse_content_type(&headers));
+    }
+
+    #[test]
+    fn detect_body_encoding() {
+        let mut headers = MessageHeaders::new();
+        assert!(!is_body_encoded(&headers));
+        assert!(has_supported_encoding(&headers));
+        headers.set_header("Content-Encoding", "identity");
+        assert!(!is_body_encoded(&headers));
+        assert!(has_supported_encoding(&headers));
+        headers.set_header("Content-Encoding", "deflate, GZIP");
+        assert!(is_body_encoded(&headers));
+        assert!(has_supported_encoding(&headers));
+        headers.set_header("Content-Encoding", "gzip, br");
+        assert!(is_body_encoded(&headers));
+        assert!(!has_supported_encoding(&headers));
+    }
+
     #[test]
     fn decode_body_gzipped() {
         let mut headers = MessageHeaders::new();
@@ -247,7 +519,7 @@ mod tests {
         headers.set_header("Content-Length", encoded_body.len().to_string());
         headers.set_header("Content-Encoding", "gzip");
         assert!(matches!(
-            decode_body(&mut headers, encoded_body),
+            decode_body(&mut headers, encoded_body, None),
             Ok(body) if body == decoded_body
         ));
         assert_eq!(
@@ -257,6 +529,58 @@ mod tests {
         assert!(!headers.has_header("Content-Encoding"));
     }
 
+    #[test]
+    fn decode_body_gzipped_at_max_decoded_size() {
+        let mut headers = MessageHeaders::new();
+        let encoded_body = &[
+            0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0A, 0xF3,
+            0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0x08, 0xCF, 0x2F, 0xCA, 0x49,
+            0x51, 0x04, 0x00, 0xD0, 0xC3, 0x4A, 0xEC, 0x0D, 0x00, 0x00, 0x00,
+        ];
+        headers.set_header("Content-Encoding", "gzip");
+        assert!(matches!(
+            decode_body(&mut headers, encoded_body, Some(13)),
+            Ok(body) if body == b"Hello, World!"
+        ));
+    }
+
+    #[test]
+    fn decode_body_gzipped_over_max_decoded_size() {
+        let mut headers = MessageHeaders::new();
+        let encoded_body = &[
+            0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0A, 0xF3,
+            0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0x08, 0xCF, 0x2F, 0xCA, 0x49,
+            0x51, 0x04, 0x00, 0xD0, 0xC3, 0x4A, 0xEC, 0x0D, 0x00, 0x00, 0x00,
+        ];
+        headers.set_header("Content-Encoding", "gzip");
+        assert!(matches!(
+            decode_body(&mut headers, encoded_body, Some(12)),
+            Err(Error::DecodedBodyTooLarge)
+        ));
+        assert_eq!(
+            Some("gzip"),
+            headers.header_value("Content-Encoding").as_deref()
+        );
+    }
+
+    #[test]
+    fn decode_body_deflate_bomb() {
+        use flate2::{
+            write::DeflateEncoder,
+            Compression,
+        };
+        use std::io::Write as _;
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0; 10_000_000]).unwrap();
+        let encoded_body = encoder.finish().unwrap();
+        let mut headers = MessageHeaders::new();
+        headers.set_header("Content-Encoding", "deflate");
+        assert!(matches!(
+            decode_body(&mut headers, encoded_body, Some(1024)),
+            Err(Error::DecodedBodyTooLarge)
+        ));
+    }
+
     #[test]
     fn decode_body_deflated_then_gzipped() {
         let mut headers = MessageHeaders::new();
@@ -270,7 +594,7 @@ mod tests {
         headers.set_header("Content-Length", encoded_body.len().to_string());
         headers.set_header("Content-Encoding", "deflate, gzip");
         assert!(matches!(
-            decode_body(&mut headers, encoded_body),
+            decode_body(&mut headers, encoded_body, None),
             Ok(body) if body == decoded_body
         ));
         assert_eq!(
@@ -292,7 +616,7 @@ mod tests {
         headers.set_header("Content-Length", encoded_body.len().to_string());
         headers.set_header("Content-Encoding", "foobar, gzip");
         assert!(matches!(
-            decode_body(&mut headers, encoded_body),
+            decode_body(&mut headers, encoded_body, None),
             Ok(body) if body == decoded_body
         ));
         assert_eq!(
@@ -305,6 +629,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_body_gzipped_then_identity() {
+        let mut headers = MessageHeaders::new();
+        let encoded_body = &[
+            0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0A, 0xF3,
+            0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0x08, 0xCF, 0x2F, 0xCA, 0x49,
+            0x51, 0x04, 0x00, 0xD0, 0xC3, 0x4A, 0xEC, 0x0D, 0x00, 0x00, 0x00,
+        ];
+        let decoded_body = b"Hello, World!";
+        headers.set_header("Content-Length", encoded_body.len().to_string());
+        headers.set_header("Content-Encoding", "gzip, identity");
+        assert!(matches!(
+            decode_body(&mut headers, encoded_body, None),
+            Ok(body) if body == decoded_body
+        ));
+        assert_eq!(
+            decoded_body.len().to_string(),
+            headers.header_value("Content-Length").unwrap()
+        );
+        assert!(!headers.has_header("Content-Encoding"));
+    }
+
     #[test]
     fn body_to_string_valid_encoding_iso_8859_1() {
         let mut headers = MessageHeaders::new();
@@ -349,4 +695,57 @@ mod tests {
             decode_body_as_text(&headers, body).as_deref()
         );
     }
+
+    #[test]
+    fn body_to_string_without_bom_handling_keeps_utf_8_bom() {
+        let mut headers = MessageHeaders::new();
+        let body = b"\xef\xbb\xbfHello, World!";
+        headers.set_header("Content-Type", "text/plain; charset=utf-8");
+        assert_eq!(
+            Some("\u{feff}Hello, World!"),
+            decode_body_as_text(&headers, body).as_deref()
+        );
+    }
+
+    #[test]
+    fn body_to_string_with_bom_handling_strips_utf_8_bom() {
+        let mut headers = MessageHeaders::new();
+        let body = b"\xef\xbb\xbfPlatform 9\xc2\xbe";
+        headers.set_header("Content-Type", "text/plain; charset=utf-8");
+        assert_eq!(
+            Some("Platform 9¾"),
+            decode_body_as_text_with_bom_handling(&headers, body).as_deref()
+        );
+    }
+
+    #[test]
+    fn body_to_string_with_bom_handling_prefers_utf_16le_bom() {
+        let mut headers = MessageHeaders::new();
+        let body = b"\xff\xfeH\x00i\x00 \x00\xbe\x00";
+        headers.set_header("Content-Type", "text/plain; charset=iso-8859-1");
+        assert_eq!(
+            Some("Hi ¾"),
+            decode_body_as_text_with_bom_handling(&headers, body).as_deref()
+        );
+    }
+
+    #[test]
+    fn body_to_string_with_bom_handling_without_bom() {
+        let mut headers = MessageHeaders::new();
+        let body =
+            b"Tickets to Hogwarts leaving from Platform 9\xbe are \xa310 each";
+        headers.set_header("Content-Type", "text/plain");
+        assert_eq!(
+            Some("Tickets to Hogwarts leaving from Platform 9¾ are £10 each"),
+            decode_body_as_text_with_bom_handling(&headers, body).as_deref()
+        );
+    }
+
+    #[test]
+    fn body_to_string_with_bom_handling_requires_text_type() {
+        let mut headers = MessageHeaders::new();
+        let body = b"\xef\xbb\xbfHello, World!";
+        headers.set_header("Content-Type", "application/octet-stream");
+        assert!(decode_body_as_text_with_bom_handling(&headers, body).is_none());
+    }
 }
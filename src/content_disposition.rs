@@ -0,0 +1,205 @@
+use super::{
+    coding::split_parameters,
+    form::percent_decode,
+    is_tchar,
+    unquote,
+};
+
+/// This represents the value of a `Content-Disposition` header, which tells
+/// the recipient whether to display the content of a message inline or save
+/// it as a file, and under what name, as defined in [IETF RFC
+/// 6266](https://tools.ietf.org/html/rfc6266).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContentDisposition {
+    /// This is the disposition type, such as `attachment`, normalized to
+    /// lowercase.
+    pub disposition_type: String,
+
+    /// This is the suggested name of the file in which to save the content,
+    /// if any.  It comes from the `filename*` parameter, decoded as described
+    /// in [IETF RFC 5987 section
+    /// 3.2](https://tools.ietf.org/html/rfc5987#section-3.2), if that's
+    /// present and can be decoded, or otherwise from the `filename`
+    /// parameter.  The name comes from the sender and is not sanitized, so
+    /// it must be treated as untrusted: it may contain path separators, such
+    /// as in `../../etc/passwd`, or other characters not allowed in file
+    /// names, and should be reduced to a safe name before use.
+    pub filename: Option<String>,
+
+    /// These are the parameters of the disposition, such as
+    /// `filename=example.txt`, as name/value pairs in the order in which they
+    /// appear.  Any quoting and escaping has been removed from the values,
+    /// but extended values such as that of `filename*` are not decoded.
+    pub parameters: Vec<(String, String)>,
+}
+
+impl ContentDisposition {
+    /// Return the value of the first parameter of the disposition with the
+    /// given name, which is matched without regard to case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::ContentDisposition;
+    ///
+    /// let content_disposition =
+    ///     ContentDisposition::parse("form-data; Name=\"field1\"").unwrap();
+    /// assert_eq!(Some("field1"), content_disposition.parameter("name"));
+    /// assert_eq!(None, content_disposition.parameter("filename"));
+    /// ```
+    #[must_use]
+    pub fn parameter<T>(
+        &self,
+        name: T,
+    ) -> Option<&str>
+    where
+        T: AsRef<str>,
+    {
+        let name = name.as_ref();
+        self.parameters.iter().find_map(|(parameter_name, value)| {
+            if parameter_name.eq_ignore_ascii_case(name) {
+                Some(value.as_str())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Parse the given text, such as the value of a `Content-Disposition`
+    /// header.  `None` is returned if the disposition type is missing or is
+    /// not a valid token.  A semicolon within a quoted parameter value, as in
+    /// `filename="a;b.txt"`, is part of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::ContentDisposition;
+    ///
+    /// let content_disposition = ContentDisposition::parse(concat!(
+    ///     "Attachment; filename=\"euro rates.txt\"; ",
+    ///     "filename*=UTF-8''%e2%82%ac%20rates.txt",
+    /// ))
+    /// .unwrap();
+    /// assert_eq!("attachment", content_disposition.disposition_type);
+    /// assert_eq!(
+    ///     Some("\u{20ac} rates.txt"),
+    ///     content_disposition.filename.as_deref()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn parse<T>(text: T) -> Option<Self>
+    where
+        T: AsRef<str>,
+    {
+        let text = text.as_ref();
+        let (disposition_type, parameters) =
+            text.split_once(';').unwrap_or((text, ""));
+        let disposition_type = disposition_type.trim();
+        if disposition_type.is_empty()
+            || !disposition_type.chars().all(is_tchar)
+        {
+            return None;
+        }
+        let mut content_disposition = Self {
+            disposition_type: disposition_type.to_ascii_lowercase(),
+            filename: None,
+            parameters: split_parameters(parameters)
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), unquote(value)))
+                .collect(),
+        };
+        content_disposition.filename = content_disposition
+            .parameter("filename*")
+            .and_then(decode_ext_value)
+            .or_else(|| {
+                content_disposition.parameter("filename").map(str::to_string)
+            })
+            .filter(|filename| !filename.is_empty());
+        Some(content_disposition)
+    }
+}
+
+// Decode an extended parameter value, made up of a character set, an
+// optional language, and the percent-encoded value, separated by single
+// quotes, as defined in [IETF RFC 5987 section
+// 3.2](https://tools.ietf.org/html/rfc5987#section-3.2).  `None` is returned
+// if the value isn't in that form, its character set isn't supported by
+// `encoding_rs`, or the decoded bytes aren't valid in that character set.
+fn decode_ext_value(ext_value: &str) -> Option<String> {
+    let mut parts = ext_value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let value = parts.next()?;
+    let encoding = encoding_rs::Encoding::for_label(charset.as_bytes())?;
+    let value = percent_decode(value.as_bytes());
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&value)
+        .map(std::borrow::Cow::into_owned)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_content_disposition_with_filename() {
+        assert_eq!(
+            Some(ContentDisposition {
+                disposition_type: "attachment".into(),
+                filename: Some("a \"b\".txt".into()),
+                parameters: vec![
+                    ("filename".into(), "a \"b\".txt".into()),
+                    ("size".into(), "42".into()),
+                ],
+            }),
+            ContentDisposition::parse(
+                "ATTACHMENT ; filename=\"a \\\"b\\\".txt\";size=42"
+            )
+        );
+    }
+
+    #[test]
+    fn parse_content_disposition_with_semicolon_in_filename() {
+        let content_disposition =
+            ContentDisposition::parse("attachment; filename=\"a;b.txt\"")
+                .unwrap();
+        assert_eq!(Some("a;b.txt"), content_disposition.filename.as_deref());
+        assert_eq!(
+            vec![("filename".to_string(), "a;b.txt".to_string())],
+            content_disposition.parameters
+        );
+    }
+
+    #[test]
+    fn parse_content_disposition_extended_filename() {
+        for (text, filename) in &[
+            ("filename*=UTF-8'en'%c3%a9t%c3%a9.txt", "\u{e9}t\u{e9}.txt"),
+            ("filename*=iso-8859-1''%e9t%e9.txt", "\u{e9}t\u{e9}.txt"),
+            ("filename=plain.txt; filename*=UTF-8''x%20y", "x y"),
+            ("filename*=UTF-8''%ff; filename=plain.txt", "plain.txt"),
+            ("filename*=bogus''x; filename=plain.txt", "plain.txt"),
+            ("filename*=x.txt; filename=plain.txt", "plain.txt"),
+        ] {
+            let content_disposition =
+                ContentDisposition::parse(format!("attachment; {text}"));
+            assert_eq!(
+                Some(*filename),
+                content_disposition.unwrap().filename.as_deref(),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_content_disposition_without_filename() {
+        for text in &["inline", "attachment; filename=\"\"", "form-data; a=b"] {
+            assert_eq!(None, ContentDisposition::parse(text).unwrap().filename);
+        }
+        assert_eq!(None, ContentDisposition::parse(""));
+        assert_eq!(None, ContentDisposition::parse("; filename=x"));
+        assert_eq!(None, ContentDisposition::parse("at tachment"));
+    }
+}
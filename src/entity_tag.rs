@@ -0,0 +1,201 @@
+/// This represents an entity tag, which is an opaque validator for a
+/// particular representation of a resource, as defined in [IETF RFC 7232
+/// section 2.3](https://tools.ietf.org/html/rfc7232#section-2.3).
+///
+/// When formatted with `{}`, the entity tag is shown as it would appear in a
+/// header, such as `W/"xyzzy"`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntityTag {
+    /// This is the opaque tag, without the double quotes which surround it
+    /// in a header.
+    pub tag: String,
+
+    /// This indicates whether or not the entity tag is a weak validator,
+    /// marked with a `W/` prefix in a header.
+    pub weak: bool,
+}
+
+impl EntityTag {
+    /// Parse the given text, such as the value of an `ETag` header, as a
+    /// single entity tag.  `None` is returned if the text is not a valid
+    /// entity tag.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::EntityTag;
+    ///
+    /// let entity_tag = EntityTag::parse("W/\"xyzzy\"").unwrap();
+    /// assert!(entity_tag.weak);
+    /// assert_eq!("xyzzy", entity_tag.tag);
+    /// assert_eq!(None, EntityTag::parse("xyzzy"));
+    /// ```
+    #[must_use]
+    pub fn parse<T>(text: T) -> Option<Self>
+    where
+        T: AsRef<str>,
+    {
+        let text = text.as_ref().trim();
+        let (weak, quoted_tag) = match text.strip_prefix("W/") {
+            Some(quoted_tag) => (true, quoted_tag),
+            None => (false, text),
+        };
+        let tag = quoted_tag.strip_prefix('"')?.strip_suffix('"')?;
+        if tag.chars().all(is_etagc) {
+            Some(Self {
+                tag: tag.to_string(),
+                weak,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Determine whether the entity tag matches the given one using the
+    /// strong comparison function of [IETF RFC 7232 section
+    /// 2.3.2](https://tools.ietf.org/html/rfc7232#section-2.3.2): both must
+    /// be strong, and their opaque tags must be identical.
+    #[must_use]
+    pub fn strong_eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Determine whether the entity tag matches the given one using the weak
+    /// comparison function of [IETF RFC 7232 section
+    /// 2.3.2](https://tools.ietf.org/html/rfc7232#section-2.3.2): their
+    /// opaque tags must be identical, regardless of whether either is weak.
+    #[must_use]
+    pub fn weak_eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl std::fmt::Display for EntityTag {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if self.weak {
+            write!(f, "W/")?;
+        }
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+// Determine whether or not the given character may appear in the opaque tag
+// of an entity tag.
+fn is_etagc(c: char) -> bool {
+    c == '!' || ('#'..='~').contains(&c) || !c.is_ascii()
+}
+
+// Parse a comma-separated list of entity tags, such as the value of an
+// `If-None-Match` header.  Since an opaque tag may itself contain commas, the
+// list is scanned one entity tag at a time rather than split on commas.
+// Malformed elements are skipped, up to the next comma.
+pub(crate) fn parse_entity_tags(list: &str) -> Vec<EntityTag> {
+    let mut entity_tags = Vec::new();
+    let mut rest = list;
+    loop {
+        rest = rest.trim_start_matches([',', ' ', '\t']);
+        if rest.is_empty() {
+            break;
+        }
+        let (weak, quoted_tag) = match rest.strip_prefix("W/") {
+            Some(quoted_tag) => (true, quoted_tag),
+            None => (false, rest),
+        };
+        if let Some((tag, after_tag)) = quoted_tag
+            .strip_prefix('"')
+            .and_then(|quoted_tag| quoted_tag.split_once('"'))
+        {
+            if tag.chars().all(is_etagc) {
+                entity_tags.push(EntityTag {
+                    tag: tag.to_string(),
+                    weak,
+                });
+            }
+            rest = after_tag;
+        } else {
+            rest = rest.split_once(',').map_or("", |(_, after)| after);
+        }
+    }
+    entity_tags
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_entity_tag() {
+        assert_eq!(
+            Some(EntityTag {
+                tag: "xyzzy".into(),
+                weak: false,
+            }),
+            EntityTag::parse("\"xyzzy\"")
+        );
+        assert_eq!(
+            Some(EntityTag {
+                tag: String::new(),
+                weak: true,
+            }),
+            EntityTag::parse("W/\"\"")
+        );
+        assert_eq!(None, EntityTag::parse("xyzzy"));
+        assert_eq!(None, EntityTag::parse("w/\"xyzzy\""));
+        assert_eq!(None, EntityTag::parse("\"xy zzy\""));
+        assert_eq!(None, EntityTag::parse("\"xyzzy"));
+    }
+
+    #[test]
+    fn format_entity_tag() {
+        for text in &["\"xyzzy\"", "W/\"xyzzy\""] {
+            assert_eq!(*text, EntityTag::parse(text).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn compare_entity_tags() {
+        let strong = EntityTag::parse("\"1\"").unwrap();
+        let weak = EntityTag::parse("W/\"1\"").unwrap();
+        let other = EntityTag::parse("\"2\"").unwrap();
+        assert!(strong.strong_eq(&strong));
+        assert!(!strong.strong_eq(&weak));
+        assert!(!weak.strong_eq(&weak));
+        assert!(!strong.strong_eq(&other));
+        assert!(strong.weak_eq(&weak));
+        assert!(weak.weak_eq(&weak));
+        assert!(!weak.weak_eq(&other));
+    }
+
+    #[test]
+    fn parse_entity_tag_list() {
+        assert_eq!(
+            vec![
+                EntityTag {
+                    tag: "a,b".into(),
+                    weak: false,
+                },
+                EntityTag {
+                    tag: "c".into(),
+                    weak: true,
+                },
+                EntityTag {
+                    tag: "d".into(),
+                    weak: false,
+                },
+            ],
+            parse_entity_tags("\"a,b\", W/\"c\" , junk, \"d\"")
+        );
+        assert!(parse_entity_tags("*").is_empty());
+    }
+}
@@ -8,6 +8,17 @@ pub enum Error {
     #[error("unable to decode response body")]
     BadContentEncoding(#[source] std::io::Error),
 
+    /// The lines of the message head ended in a line feed alone, rather than
+    /// a carriage return and line feed, which some implementations treat
+    /// differently, making it a possible sign of a request smuggling attempt.
+    #[error("message head lines end in bare line feeds")]
+    BareLineFeed,
+
+    /// The chunk extensions in a chunk size line are longer than the limit
+    /// set for them.
+    #[error("chunk extensions are too long")]
+    ChunkExtensionTooLong,
+
     /// The attached bytes did not parse as valid chunk size text.
     #[error("chunk size line is not valid text")]
     ChunkSizeLineNotValidText {
@@ -19,18 +30,93 @@ pub enum Error {
         source: Utf8Error,
     },
 
+    /// The value of the `Content-Length` header of the message does not match
+    /// the length of its body.
+    #[error("Content-Length header value does not match body length")]
+    ContentLengthMismatch {
+        /// This is the value of the `Content-Length` header.
+        content_length: usize,
+
+        /// This is the length of the body, in bytes.
+        body_length: usize,
+    },
+
+    /// The message has both a `Content-Length` header and the "chunked"
+    /// transfer coding, which is a sign of a request smuggling attempt.
+    #[error("message has both Content-Length and chunked transfer coding")]
+    ChunkedWithContentLength,
+
+    /// The message has more than one `Content-Length` value, and they don't
+    /// all agree.  The attached values are the ones found, in order.
+    #[error("conflicting Content-Length header values")]
+    ConflictingContentLength(Vec<String>),
+
+    /// The `Content-Length` header value is valid, but is too large to be
+    /// represented as a `usize` on this platform.
+    #[error("Content-Length header value is too large")]
+    ContentLengthTooLarge(u64),
+
+    /// The body, once its content coding was reversed, was larger than the
+    /// configured maximum decoded size.
+    #[error("decoded body is too large")]
+    DecodedBodyTooLarge,
+
+    /// The attached header appeared more than once in a message, where at
+    /// most one is permitted.
+    #[error("duplicate header")]
+    DuplicateHeader(String),
+
+    /// An attempt was made to encode an empty chunk of a chunked body, which
+    /// would be mistaken for the last chunk, prematurely terminating the body.
+    #[error("cannot encode an empty chunk")]
+    EmptyChunk,
+
+    /// A header is too long to be folded to fit within the line limit used
+    /// to generate the message, because its value has no whitespace at
+    /// which to fold it where it would be needed.
+    #[error("header {name} cannot be folded to fit within {line_limit} bytes")]
+    HeaderNotFoldable {
+        /// This is the name of the header which could not be folded.
+        name: String,
+
+        /// This is the line limit, in bytes, which the header could not be
+        /// folded to fit within.
+        line_limit: usize,
+    },
+
     /// An error occurred with the message headers.
     #[error("Error in headers")]
     Headers(#[source] rhymessage::Error),
 
+    /// The input ended before the message was complete.
+    #[error("incomplete message")]
+    IncompleteMessage,
+
+    /// The attached header name contains whitespace, such as a space before
+    /// the colon which ends it, which some implementations strip and others
+    /// don't, making it a possible sign of a request smuggling attempt.
+    #[error("whitespace in header name")]
+    HeaderNameWhitespace(String),
+
+    /// The chunk extensions in the attached chunk size line were invalid.
+    #[error("invalid chunk extension")]
+    InvalidChunkExtension(String),
+
     /// A chunk size in the body was invalid.
     #[error("invalid chunk size value")]
     InvalidChunkSize(std::num::ParseIntError),
 
-    /// The attached bytes appeared after the end of a chunk
-    /// in the place where a carriage-return and line-feed were expected.
+    /// Extra junk appeared after the end of a chunk in the place where a
+    /// carriage-return and line-feed were expected.
     #[error("unexpected extra junk at the end of a chunk")]
-    InvalidChunkTerminator(Vec<u8>),
+    InvalidChunkTerminator {
+        /// These are the first few bytes of the junk (at most 16 of them).
+        junk: Vec<u8>,
+
+        /// This is the offset, in bytes from the start of the chunked body,
+        /// where the junk was found.
+        offset: usize,
+    },
 
     /// The `Content-Length` header value is not valid.
     #[error("invalid Content-Length header value")]
@@ -40,10 +126,43 @@ pub enum Error {
     #[error("invalid status code")]
     InvalidStatusCode(std::num::ParseIntError),
 
+    /// The attached request method is not a valid token.
+    #[error("invalid request method")]
+    InvalidMethod(String),
+
+    /// The attached request target is not in any of the forms permitted
+    /// for the request method.
+    #[error("invalid request target")]
+    InvalidRequestTarget(String),
+
+    /// The attached user name can't be presented using the "Basic"
+    /// authentication scheme, because it contains a colon, which would be
+    /// taken as the end of the user name.
+    #[error("user name contains a colon")]
+    InvalidUserName(String),
+
+    /// The `Location` header value of a response is not a valid URI.
+    #[error("invalid Location header URI")]
+    LocationUriInvalid(#[source] rhymuri::Error),
+
     /// The message is too large to fit within the configured size limit.
     #[error("message exceeds maximum size limit")]
     MessageTooLong,
 
+    /// The request has no `Host` header, which is required in HTTP/1.1
+    /// requests.
+    #[error("missing Host header")]
+    MissingHostHeader,
+
+    /// A redirect response has no `Location` header.
+    #[error("redirect response has no Location header")]
+    MissingLocationHeader,
+
+    /// The attached URI can't be used as the target of a request sent to a
+    /// proxy, because it isn't absolute (it has no scheme or no authority).
+    #[error("proxy target URI is not absolute")]
+    ProxyTargetNotAbsolute(String),
+
     /// No delimiter was found to parse the method from the attached HTTP
     /// request line.
     #[error("unable to find method delimiter in request line")]
@@ -90,6 +209,12 @@ pub enum Error {
     #[error("invalid request target URI")]
     RequestTargetUriInvalid(#[from] rhymuri::Error),
 
+    /// The value of the "Content-Length" header, attached, has a sign, such
+    /// as `-1` or `+5`.  This is distinguished from other invalid values
+    /// since it may indicate an attempt at request smuggling.
+    #[error("signed Content-Length value: {0}")]
+    SignedContentLength(String),
+
     /// The attached status code was out of range.
     #[error("status code is out of range")]
     StatusCodeOutOfRange(usize),
@@ -121,11 +246,232 @@ pub enum Error {
     #[error("unrecognized protocol in status line")]
     StatusLineProtocol(String),
 
+    /// The attached bytes are the beginning of the status line, whose length
+    /// exceeds the status line limit.
+    #[error("status line too long")]
+    StatusLineTooLong(Vec<u8>),
+
     /// An error occurred during string formatting.
     #[error("error during string format")]
     StringFormat(#[from] std::io::Error),
 
+    /// The chunked body contains more chunks than the configured limit.
+    #[error("chunked body contains too many chunks")]
+    TooManyChunks,
+
     /// An error occurred with the message trailer.
     #[error("Error in trailer")]
     Trailer(#[source] rhymessage::Error),
+
+    /// The message has both a `Content-Length` header and a
+    /// `Transfer-Encoding` header, with any transfer codings, which is a
+    /// sign of a request smuggling attempt.
+    #[error("message has both Content-Length and Transfer-Encoding")]
+    TransferEncodingWithContentLength,
+
+    /// The request has a body, framed by a nonzero `Content-Length` or by a
+    /// `Transfer-Encoding` header, but its method is the attached one, which
+    /// is configured to reject bodies.
+    #[error("unexpected body in request")]
+    UnexpectedRequestBody(String),
+
+    /// The attached number of bytes followed a body whose length was given
+    /// by the `Content-Length` header, in the same input, where no more
+    /// bytes were expected.
+    #[error("unexpected data after message body")]
+    UnexpectedTrailingData(usize),
+
+    /// An error occurred writing the message to the given writer.
+    #[error("unable to write message")]
+    Write(#[source] std::io::Error),
+}
+
+impl Error {
+    /// Return the raw bytes of the line which caused the error, if it is one
+    /// of the errors about a malformed request line, status line, chunk size
+    /// line, or header line.  For errors which carry the line as text, the
+    /// bytes of that text are returned.  For errors about a line which is too
+    /// long, only the part of the line scanned so far is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Request;
+    ///
+    /// let mut request = Request::new();
+    /// let error = request.parse(b"GET /\xff HTTP/1.1\r\n").unwrap_err();
+    /// assert_eq!(Some(&b"GET /\xff HTTP/1.1"[..]), error.offending_bytes());
+    /// ```
+    #[must_use]
+    pub fn offending_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Error::RequestLineNoMethodDelimiter(line)
+            | Error::RequestLineNoMethodOrExtraWhitespace(line)
+            | Error::RequestLineNoTargetDelimiter(line)
+            | Error::RequestLineNoTargetOrExtraWhitespace(line)
+            | Error::RequestLineProtocol(line)
+            | Error::StatusLineNoProtocolDelimiter(line)
+            | Error::StatusLineNoStatusCodeDelimiter(line)
+            | Error::StatusLineProtocol(line)
+            | Error::Headers(
+                rhymessage::Error::HeaderLineCouldNotBeFolded(line)
+                | rhymessage::Error::HeaderLineMissingColon(line),
+            )
+            | Error::Trailer(
+                rhymessage::Error::HeaderLineCouldNotBeFolded(line)
+                | rhymessage::Error::HeaderLineMissingColon(line),
+            ) => Some(line.as_bytes()),
+            Error::ChunkSizeLineNotValidText {
+                chunk_size_line: line,
+                ..
+            }
+            | Error::RequestLineNotValidText {
+                request_line: line,
+                ..
+            }
+            | Error::StatusLineNotValidText {
+                status_line: line,
+                ..
+            }
+            | Error::RequestLineTooLong(line)
+            | Error::StatusLineTooLong(line)
+            | Error::Headers(
+                rhymessage::Error::HeaderLineInvalidText {
+                    line,
+                    ..
+                }
+                | rhymessage::Error::HeaderLineTooLong(line),
+            )
+            | Error::Trailer(
+                rhymessage::Error::HeaderLineInvalidText {
+                    line,
+                    ..
+                }
+                | rhymessage::Error::HeaderLineTooLong(line),
+            ) => Some(line),
+            _ => None,
+        }
+    }
+
+    /// Return the line which caused the error as text, for logging, if it is
+    /// one of the errors for which [`offending_bytes`](#method.offending_bytes)
+    /// returns the line.  Any bytes which are not valid UTF-8 are replaced
+    /// with the Unicode replacement character.
+    #[must_use]
+    pub fn offending_text_lossy(&self) -> Option<String> {
+        self.offending_bytes()
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+    }
+}
+
+impl From<Error> for std::io::Error {
+    /// Convert the error into an I/O error, so that it may be propagated by
+    /// I/O adapters.  Errors caused by exceeding a configured limit are given
+    /// the kind `Other`, errors caused by the caller are given the kind
+    /// `InvalidInput`, and errors caused by invalid message text are given the
+    /// kind `InvalidData`.  The original error is kept as the inner error.
+    fn from(error: Error) -> Self {
+        let kind = match &error {
+            Error::StringFormat(error) | Error::Write(error) => error.kind(),
+            Error::ChunkExtensionTooLong
+            | Error::ContentLengthTooLarge(_)
+            | Error::DecodedBodyTooLarge
+            | Error::HeaderNotFoldable {
+                ..
+            }
+            | Error::MessageTooLong
+            | Error::RequestLineTooLong(_)
+            | Error::StatusLineTooLong(_)
+            | Error::TooManyChunks
+            | Error::Headers(rhymessage::Error::HeaderLineTooLong(_))
+            | Error::Trailer(rhymessage::Error::HeaderLineTooLong(_)) => {
+                std::io::ErrorKind::Other
+            },
+            Error::ContentLengthMismatch {
+                ..
+            }
+            | Error::EmptyChunk
+            | Error::InvalidMethod(_)
+            | Error::InvalidRequestTarget(_)
+            | Error::InvalidUserName(_)
+            | Error::ProxyTargetNotAbsolute(_) => {
+                std::io::ErrorKind::InvalidInput
+            },
+            _ => std::io::ErrorKind::InvalidData,
+        };
+        Self::new(kind, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn into_io_error_invalid_data() {
+        let error: std::io::Error =
+            Error::StatusLineProtocol("HTTP/1.0 200 OK".into()).into();
+        assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+        assert_eq!("unrecognized protocol in status line", error.to_string());
+        assert!(matches!(
+            error.into_inner().unwrap().downcast::<Error>().as_deref(),
+            Ok(Error::StatusLineProtocol(line)) if line == "HTTP/1.0 200 OK"
+        ));
+    }
+
+    #[test]
+    fn into_io_error_size_limit() {
+        let error: std::io::Error = Error::MessageTooLong.into();
+        assert_eq!(std::io::ErrorKind::Other, error.kind());
+        assert_eq!("message exceeds maximum size limit", error.to_string());
+        let error: std::io::Error =
+            Error::Headers(rhymessage::Error::HeaderLineTooLong(Vec::new()))
+                .into();
+        assert_eq!(std::io::ErrorKind::Other, error.kind());
+    }
+
+    #[test]
+    fn into_io_error_string_format() {
+        let error: std::io::Error = Error::StringFormat(std::io::Error::new(
+            std::io::ErrorKind::WriteZero,
+            "oops",
+        ))
+        .into();
+        assert_eq!(std::io::ErrorKind::WriteZero, error.kind());
+    }
+
+    #[test]
+    fn offending_line_of_text_error() {
+        let error = Error::StatusLineProtocol("HTTP/1.0 200 OK".into());
+        assert_eq!(Some(&b"HTTP/1.0 200 OK"[..]), error.offending_bytes());
+        assert_eq!(
+            Some("HTTP/1.0 200 OK"),
+            error.offending_text_lossy().as_deref()
+        );
+    }
+
+    #[test]
+    fn offending_line_of_raw_bytes_error() {
+        let error = Error::RequestLineNotValidText {
+            request_line: b"GET /\xff HTTP/1.1".to_vec(),
+            source: String::from_utf8(vec![0xFF]).unwrap_err().utf8_error(),
+        };
+        assert_eq!(Some(&b"GET /\xff HTTP/1.1"[..]), error.offending_bytes());
+        assert_eq!(
+            Some("GET /\u{fffd} HTTP/1.1"),
+            error.offending_text_lossy().as_deref()
+        );
+        let error =
+            Error::Trailer(rhymessage::Error::HeaderLineTooLong(b"X-".to_vec()));
+        assert_eq!(Some(&b"X-"[..]), error.offending_bytes());
+    }
+
+    #[test]
+    fn no_offending_line() {
+        let error = Error::MessageTooLong;
+        assert_eq!(None, error.offending_bytes());
+        assert_eq!(None, error.offending_text_lossy());
+    }
 }
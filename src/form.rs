@@ -0,0 +1,80 @@
+// Decode the percent-encoded octets in the given text, as described in [IETF
+// RFC 3986 section 2.1](https://tools.ietf.org/html/rfc3986#section-2.1).  A
+// percent sign which isn't followed by two hexadecimal digits is left as is.
+pub(crate) fn percent_decode(text: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        let hex_digits = text
+            .get(i + 1..i + 3)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()));
+        match (text[i], hex_digits) {
+            (b'%', Some(hex_digits)) => {
+                decoded.extend(u8::from_str_radix(hex_digits, 16).ok());
+                i += 3;
+            },
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            },
+        }
+    }
+    decoded
+}
+
+// Decode one name or value of `application/x-www-form-urlencoded` text, in
+// which each plus sign stands for a space.  Any bytes which aren't valid
+// UTF-8 once decoded are replaced.
+fn decode_component(component: &[u8]) -> String {
+    let component = component
+        .iter()
+        .map(|&b| if b == b'+' { b' ' } else { b })
+        .collect::<Vec<_>>();
+    String::from_utf8_lossy(&percent_decode(&component)).into_owned()
+}
+
+// Parse the given `application/x-www-form-urlencoded` text, such as the query
+// of a URI, into its name/value pairs, in the order in which they appear, as
+// described in the [URL
+// Standard](https://url.spec.whatwg.org/#urlencoded-parsing).  A pair without
+// an equals sign has an empty value, and empty pairs are skipped.
+pub(crate) fn parse_form_urlencoded(text: &[u8]) -> Vec<(String, String)> {
+    text.split(|&b| b == b'&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = match pair.iter().position(|&b| b == b'=') {
+                Some(delimiter) => (&pair[..delimiter], &pair[delimiter + 1..]),
+                None => (pair, &[][..]),
+            };
+            (decode_component(name), decode_component(value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn percent_decode_leaves_invalid_escapes() {
+        assert_eq!(b"A%2%zz%", &percent_decode(b"%41%2%zz%")[..]);
+        assert_eq!(b"\xff+", &percent_decode(b"%fF+")[..]);
+    }
+
+    #[test]
+    fn parse_form_pairs() {
+        assert_eq!(
+            vec![
+                ("say".to_string(), "Hi Mom".to_string()),
+                ("a&b".to_string(), "=".to_string()),
+                ("flag".to_string(), String::new()),
+                ("say".to_string(), "1+1".to_string()),
+                (String::new(), "x".to_string()),
+            ],
+            parse_form_urlencoded(b"say=Hi+Mom&a%26b=%3D&&flag&say=1%2B1&=x")
+        );
+        assert!(parse_form_urlencoded(b"").is_empty());
+    }
+}
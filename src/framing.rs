@@ -0,0 +1,28 @@
+/// This enumerates the ways in which the length of the body of a message can
+/// be determined, as described in [IETF RFC 7230 section
+/// 3.3.3](https://tools.ietf.org/html/rfc7230#section-3.3.3).  It reports
+/// the decision the parser made once the headers of a message were parsed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Framing {
+    /// The body has the attached length, in bytes, given by the
+    /// `Content-Length` header.
+    FixedLength(usize),
+
+    /// The body uses the "chunked" transfer coding.
+    Chunked,
+
+    /// The message has neither a `Content-Length` header nor the "chunked"
+    /// transfer coding, so its body extends until the connection is closed.
+    /// The parser completes the message without a body in this case, unless
+    /// the response is
+    /// [close-delimited](struct.Response.html#method.set_close_delimited),
+    /// or is [parsed for](struct.Response.html#method.parse_for_request) a
+    /// request which used HTTP/1.0.
+    UntilClose,
+
+    /// The message can't have a body, for example because of its status code
+    /// or the method of the corresponding request, or the headers of the
+    /// message have not yet been parsed.  This is the default.
+    #[default]
+    None,
+}
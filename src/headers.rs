@@ -0,0 +1,101 @@
+use super::error::Error;
+use rhymessage::MessageHeaders;
+
+/// Parse a bare block of message headers, such as a chunked body trailer or
+/// the headers exchanged inside a tunnel, without a request or status line
+/// preceding them.  The header block ends with an empty line.  If not None,
+/// the given line limit constrains the length of each header line, in the
+/// same way as for the headers of a [`Request`](struct.Request.html).
+///
+/// If the end of the header block is found, the parsed headers are returned
+/// along with the number of bytes of input consumed.  Otherwise, `None` is
+/// returned, and the user is expected to call this function again with more
+/// input.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate rhymuweb;
+/// use rhymuweb::parse_headers;
+///
+/// # fn main() -> Result<(), rhymuweb::Error> {
+/// let raw_headers = concat!("X-Foo: Bar\r\n", "X-Spam: Eggs\r\n", "\r\n",);
+/// let (headers, consumed) = parse_headers(raw_headers, Some(1000))?.unwrap();
+/// assert_eq!(raw_headers.len(), consumed);
+/// assert_eq!(Some("Bar"), headers.header_value("X-Foo").as_deref());
+/// assert_eq!(Some("Eggs"), headers.header_value("X-Spam").as_deref());
+/// assert!(parse_headers("X-Foo: Bar\r\n", Some(1000))?.is_none());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// [`Error::Headers`](enum.Error.html#variant.Headers) is returned if an
+/// error occurs parsing the headers, including if a header line exceeds the
+/// given line limit.
+pub fn parse_headers<T>(
+    raw_headers: T,
+    line_limit: Option<usize>,
+) -> Result<Option<(MessageHeaders, usize)>, Error>
+where
+    T: AsRef<[u8]>,
+{
+    let mut headers = MessageHeaders::new();
+    headers.set_line_limit(line_limit);
+    let parse_results =
+        headers.parse(raw_headers.as_ref()).map_err(Error::Headers)?;
+    match parse_results.status {
+        rhymessage::ParseStatus::Complete => {
+            Ok(Some((headers, parse_results.consumed)))
+        },
+        rhymessage::ParseStatus::Incomplete => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_complete_headers() {
+        let raw_headers = concat!(
+            "X-Foo: Bar\r\n",
+            "X-Spam: Eggs\r\n",
+            "\r\n",
+            "This is not a header",
+        );
+        let (headers, consumed) =
+            parse_headers(raw_headers, Some(1000)).unwrap().unwrap();
+        assert_eq!(raw_headers.len() - 20, consumed);
+        assert_eq!(Some("Bar"), headers.header_value("X-Foo").as_deref());
+        assert_eq!(Some("Eggs"), headers.header_value("X-Spam").as_deref());
+    }
+
+    #[test]
+    fn parse_incomplete_headers() {
+        assert!(matches!(
+            parse_headers("X-Foo: Bar\r\n", Some(1000)),
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn parse_header_line_too_long() {
+        let raw_headers = "X-Foo: This header line is way too long\r\n\r\n";
+        assert!(matches!(
+            parse_headers(raw_headers, Some(20)),
+            Err(Error::Headers(rhymessage::Error::HeaderLineTooLong(_)))
+        ));
+        assert!(matches!(parse_headers(raw_headers, None), Ok(Some(_))));
+    }
+
+    #[test]
+    fn parse_damaged_header() {
+        assert!(matches!(
+            parse_headers("X-Foo Bar\r\n\r\n", None),
+            Err(Error::Headers(rhymessage::Error::HeaderLineMissingColon(_)))
+        ));
+    }
+}
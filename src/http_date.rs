@@ -0,0 +1,269 @@
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+// These are the names of the days of the week, abbreviated and in full, and of
+// the months, as they appear in an HTTP-date.
+const DAY_NAMES: &[&str] = &["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const FULL_DAY_NAMES: &[&str] = &[
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const MONTH_NAMES: &[&str] = &[
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct",
+    "Nov", "Dec",
+];
+
+// Parse the given text as a number with exactly the given count of decimal
+// digits.
+fn parse_digits(
+    text: &str,
+    count: usize,
+) -> Option<u32> {
+    if text.len() == count && text.bytes().all(|b| b.is_ascii_digit()) {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+// Count the days from the Unix epoch to the given date of the proleptic
+// Gregorian calendar, which may be negative for dates before the epoch.  The
+// month is from 1 to 12.  This follows the `days_from_civil` algorithm by
+// Howard Hinnant.
+fn days_from_civil(
+    year: u32,
+    month: u32,
+    day: u32,
+) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5
+        + i64::from(day)
+        - 1;
+    let day_of_era =
+        year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Parse the given time of day, in the form `HH:MM:SS`, into the number of
+// seconds since midnight.  A leap second is allowed.
+fn parse_time_of_day(time_of_day: &str) -> Option<u32> {
+    let mut parts = time_of_day.split(':');
+    let hour = parse_digits(parts.next()?, 2).filter(|&hour| hour < 24)?;
+    let minute = parse_digits(parts.next()?, 2).filter(|&minute| minute < 60)?;
+    let second = parse_digits(parts.next()?, 2).filter(|&second| second <= 60)?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(hour * 3600 + minute * 60 + second)
+}
+
+// Parse the given month name, such as `Nov`, into the number of the month,
+// from 1 to 12.
+fn parse_month_name(month_name: &str) -> Option<u32> {
+    MONTH_NAMES
+        .iter()
+        .zip(1..)
+        .find_map(|(&name, month)| (name == month_name).then_some(month))
+}
+
+// Parse the given text as a date in the preferred IMF-fixdate format, such as
+// `Sun, 06 Nov 1994 08:49:37 GMT`, returning the year, month, day, and time
+// of day in seconds.
+fn parse_imf_fixdate(text: &str) -> Option<(u32, u32, u32, u32)> {
+    let mut parts = text.split(' ');
+    let day_name = parts.next()?.strip_suffix(',')?;
+    if !DAY_NAMES.contains(&day_name) {
+        return None;
+    }
+    let day = parse_digits(parts.next()?, 2)?;
+    let month = parse_month_name(parts.next()?)?;
+    let year = parse_digits(parts.next()?, 4)?;
+    let time_of_day = parse_time_of_day(parts.next()?)?;
+    if parts.next()? != "GMT" || parts.next().is_some() {
+        return None;
+    }
+    Some((year, month, day, time_of_day))
+}
+
+// Parse the given text as a date in the obsolete RFC 850 format, such as
+// `Sunday, 06-Nov-94 08:49:37 GMT`, returning the year, month, day, and time
+// of day in seconds.  Since the year has only two digits, it's taken to be in
+// the current century, or in the one before if that would put it more than 50
+// years in the future, as required by [IETF RFC 7231 section
+// 7.1.1.1](https://tools.ietf.org/html/rfc7231#section-7.1.1.1).
+fn parse_rfc850_date(text: &str) -> Option<(u32, u32, u32, u32)> {
+    let mut parts = text.split(' ');
+    let day_name = parts.next()?.strip_suffix(',')?;
+    if !FULL_DAY_NAMES.contains(&day_name) {
+        return None;
+    }
+    let mut date = parts.next()?.split('-');
+    let day = parse_digits(date.next()?, 2)?;
+    let month = parse_month_name(date.next()?)?;
+    let year = parse_digits(date.next()?, 2)?;
+    if date.next().is_some() {
+        return None;
+    }
+    let time_of_day = parse_time_of_day(parts.next()?)?;
+    if parts.next()? != "GMT" || parts.next().is_some() {
+        return None;
+    }
+    let current_year = current_year();
+    let mut year = current_year - current_year % 100 + year;
+    if year > current_year + 50 {
+        year -= 100;
+    }
+    Some((year, month, day, time_of_day))
+}
+
+// Parse the given text as a date in the obsolete format of the C `asctime`
+// function, such as `Sun Nov  6 08:49:37 1994`, returning the year, month,
+// day, and time of day in seconds.  A day of the month before the 10th is
+// padded with a space rather than a zero.
+fn parse_asctime_date(text: &str) -> Option<(u32, u32, u32, u32)> {
+    if text.len() != 24 || !text.is_ascii() {
+        return None;
+    }
+    if [3, 7, 10, 19].iter().any(|&i| text.as_bytes()[i] != b' ')
+        || !DAY_NAMES.contains(&&text[0..3])
+    {
+        return None;
+    }
+    let month = parse_month_name(&text[4..7])?;
+    let day = match text[8..10].strip_prefix(' ') {
+        Some(day) => parse_digits(day, 1)?,
+        None => parse_digits(&text[8..10], 2)?,
+    };
+    let time_of_day = parse_time_of_day(&text[11..19])?;
+    let year = parse_digits(&text[20..24], 4)?;
+    Some((year, month, day, time_of_day))
+}
+
+// Determine the current year of the Gregorian calendar, according to the
+// system clock.
+fn current_year() -> u32 {
+    let now = SystemTime::now();
+    let mut year = 1970;
+    while system_time(year + 1, 1, 1, 0).is_some_and(|start| start <= now) {
+        year += 1;
+    }
+    year
+}
+
+// Convert the given date of the proleptic Gregorian calendar and time of day,
+// in seconds since midnight, into a point in time.
+fn system_time(
+    year: u32,
+    month: u32,
+    day: u32,
+    time_of_day: u32,
+) -> Option<SystemTime> {
+    let seconds = days_from_civil(year, month, day) * 86_400
+        + i64::from(time_of_day);
+    let offset = Duration::from_secs(seconds.unsigned_abs());
+    if seconds < 0 {
+        SystemTime::UNIX_EPOCH.checked_sub(offset)
+    } else {
+        SystemTime::UNIX_EPOCH.checked_add(offset)
+    }
+}
+
+// Parse the given text as an HTTP-date, as defined in [IETF RFC 7231 section
+// 7.1.1.1](https://tools.ietf.org/html/rfc7231#section-7.1.1.1).  The
+// preferred IMF-fixdate format, such as `Sun, 06 Nov 1994 08:49:37 GMT`, is
+// accepted, as are the two obsolete formats which recipients are required to
+// accept: the RFC 850 format, such as `Sunday, 06-Nov-94 08:49:37 GMT`, and
+// the format of the C `asctime` function, such as `Sun Nov  6 08:49:37 1994`.
+// `None` is returned if the text is not in any of these formats or is not a
+// valid date and time.
+pub(crate) fn parse_http_date(text: &str) -> Option<SystemTime> {
+    let text = text.trim();
+    let (year, month, day, time_of_day) = parse_imf_fixdate(text)
+        .or_else(|| parse_rfc850_date(text))
+        .or_else(|| parse_asctime_date(text))?;
+    let days_in_month = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if day == 0 || day > days_in_month {
+        return None;
+    }
+    system_time(year, month, day, time_of_day)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_imf_fixdate() {
+        assert_eq!(
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777)),
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT")
+        );
+        assert_eq!(
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(951_782_401)),
+            parse_http_date("Tue, 29 Feb 2000 00:00:01 GMT")
+        );
+        assert_eq!(
+            Some(SystemTime::UNIX_EPOCH - Duration::from_secs(1)),
+            parse_http_date("Wed, 31 Dec 1969 23:59:59 GMT")
+        );
+    }
+
+    #[test]
+    fn parse_obsolete_date_formats() {
+        let expected =
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777));
+        assert_eq!(expected, parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"));
+        assert_eq!(expected, parse_http_date("Sun Nov  6 08:49:37 1994"));
+        assert_eq!(
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000)),
+            parse_http_date("Sunday, 09-Sep-01 01:46:40 GMT")
+        );
+        assert_eq!(
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_602_806_399)),
+            parse_http_date("Thu Oct 15 23:59:59 2020")
+        );
+    }
+
+    #[test]
+    fn parse_invalid_http_dates() {
+        for text in &[
+            "",
+            "Sun, 06 Nov 1994 08:49:37",
+            "Sun, 06 Nov 1994 08:49:37 UTC",
+            "Sun 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06 Nov 1994 08:49:37 GMT",
+            "Sun, 6 Nov 1994 08:49:37 GMT",
+            "Sun, 06 nov 1994 08:49:37 GMT",
+            "Sun, 06 Nov 94 08:49:37 GMT",
+            "Sun, 06 Nov 1994 24:00:00 GMT",
+            "Sun, 06 Nov 1994 08:49 GMT",
+            "Sun, 31 Nov 1994 08:49:37 GMT",
+            "Mon, 29 Feb 1900 08:49:37 GMT",
+            "Sun, 06 Nov 1994 08:49:37 GMT junk",
+            "Sun, 06-Nov-94 08:49:37 GMT",
+            "Sunday, 06-Nov-1994 08:49:37 GMT",
+            "Sunday, 6-Nov-94 08:49:37 GMT",
+            "Sun Nov 6 08:49:37 1994",
+            "Sun Nov  6 08:49:37 94",
+            "Sun Nov 31 08:49:37 1994",
+        ] {
+            assert_eq!(None, parse_http_date(text), "{text}");
+        }
+    }
+}
@@ -23,45 +23,100 @@
 //! * header lines: 1000 bytes each
 //! * message overall: 10,000,000 bytes
 //!
+//! Similarly, the status line of a [`Response`] is limited to 1000 bytes by
+//! default, which may be changed or removed by setting the
+//! [`status_line_limit`] field of [`Response`].
+//!
 //! To change or remove the header line length constraint, use the
 //! [`MessageHeaders::set_line_limit`] function on the [`headers`] field of
 //! [`Request`].  To change or remove the overall message length constraint,
-//! set the [`max_message_size`] field of [`Request`].
+//! set the [`max_message_size`] field of [`Request`].  To apply all of these
+//! limits at once, fill in a [`ParserConfig`] and construct the value with
+//! [`Request::with_config`] or [`Response::with_config`].
+//!
+//! If the optional `bytes` feature is enabled, requests may also be parsed
+//! from [`bytes::Bytes`](https://docs.rs/bytes) buffers via
+//! `Request::parse_bytes`, which avoids copying the body when it is
+//! contained entirely within the given buffer.
+//!
+//! If the optional `test-util` feature is enabled, `Request::assert_roundtrip`
+//! is available for tests which check that a request survives being parsed
+//! and generated again.
 //!
 //! [`headers`]: struct.Request.html#structfield.headers
 //! [`max_message_size`]: struct.Request.html#structfield.max_message_size
+//! [`status_line_limit`]: struct.Response.html#structfield.status_line_limit
+//! [`ParserConfig`]: struct.ParserConfig.html
 //! [`MessageHeaders::set_line_limit`]: https://docs.rs/rhymessage/1.3.1/rhymessage/struct.MessageHeaders.html#method.set_line_limit
 //! [`Response`]: struct.Response.html
 //! [`Response::generate`]: struct.Response.html#method.generate
 //! [`Response::new`]: struct.Response.html#method.new
 //! [`Response::parse`]: struct.Response.html#method.parse
+//! [`Response::with_config`]: struct.Response.html#method.with_config
 //! [`Request`]: struct.Request.html
 //! [`Request::generate`]: struct.Request.html#method.generate
 //! [`Request::new`]: struct.Request.html#method.new
 //! [`Request::parse`]: struct.Request.html#method.parse
+//! [`Request::with_config`]: struct.Request.html#method.with_config
 
 #![warn(clippy::pedantic)]
 #![allow(clippy::non_ascii_literal)]
 #![warn(missing_docs)]
 
+mod base64;
+mod challenge;
 mod chunked_body;
 pub mod coding;
+mod content_disposition;
+mod entity_tag;
 mod error;
+mod form;
+mod framing;
+mod headers;
+mod http_date;
+mod line_terminator;
+mod media_range;
+mod media_type;
+mod message_stats;
+mod method;
+mod on_oversize_body;
+mod parser_config;
+mod rechunker;
 mod request;
 mod response;
+mod retry_after;
+mod trailer_merge_strategy;
 
 pub use crate::{
+    challenge::Challenge,
+    content_disposition::ContentDisposition,
+    entity_tag::EntityTag,
     error::Error,
+    framing::Framing,
+    headers::parse_headers,
+    line_terminator::LineTerminator,
+    media_range::MediaRange,
+    media_type::MediaType,
+    message_stats::MessageStats,
+    method::Method,
+    on_oversize_body::OnOversizeBody,
+    parser_config::ParserConfig,
+    rechunker::Rechunker,
     request::{
+        Awaiting as RequestAwaiting,
+        ParsePhase as RequestParsePhase,
         ParseResults as RequestParseResults,
         ParseStatus as RequestParseStatus,
         Request,
     },
     response::{
+        ParsePhase as ResponseParsePhase,
         ParseResults as ResponseParseResults,
         ParseStatus as ResponseParseStatus,
         Response,
     },
+    retry_after::RetryAfter,
+    trailer_merge_strategy::TrailerMergeStrategy,
 };
 
 // This is the character sequence corresponding to a carriage return (CR)
@@ -69,15 +124,309 @@ pub use crate::{
 // line of an HTTP request.
 const CRLF: &str = "\r\n";
 
-fn find_crlf<T>(message: T) -> Option<usize>
+// This is the maximum number of bytes of a message body shown in the
+// compact debug formatting of a request or response.
+const BODY_PREVIEW_LENGTH: usize = 32;
+
+// This is the maximum number of bytes reserved for a message body in advance,
+// based on the length declared for it, so that a peer can't cause a huge
+// allocation just by declaring a huge length.  The body still grows as needed
+// beyond this as it is received.
+const MAX_BODY_PREALLOCATION: usize = 1 << 20;
+
+// This is a function registered on a request or response which is called with
+// each segment of the body as it is parsed.
+type BodyObserver = Box<dyn FnMut(&[u8]) + Send>;
+
+// This is used in the compact debug formatting of a request or response to
+// show the length of the body along with a short preview of it, rather than
+// the entire body.
+struct BodyPreview<'a>(&'a [u8]);
+
+impl std::fmt::Debug for BodyPreview<'_> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let preview = &self.0[..self.0.len().min(BODY_PREVIEW_LENGTH)];
+        write!(f, "{} bytes \"{}\"", self.0.len(), preview.escape_ascii())?;
+        if preview.len() < self.0.len() {
+            write!(f, "...")?;
+        }
+        Ok(())
+    }
+}
+
+// These are the headers which describe how the body of a message is framed
+// or encoded, and so no longer apply once the body is cleared.
+const BODY_FRAMING_HEADERS: &[&str] =
+    &["Content-Encoding", "Content-Length", "Transfer-Encoding"];
+
+// These are the headers which are always hop-by-hop, meaning they apply only
+// to a single connection and must not be forwarded by a proxy, as listed in
+// [IETF RFC 7230 section 6.1](https://tools.ietf.org/html/rfc7230#section-6.1)
+// and [IETF RFC 2616 section
+// 13.5.1](https://tools.ietf.org/html/rfc2616#section-13.5.1).
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "Connection",
+    "Keep-Alive",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "TE",
+    "Trailer",
+    "Transfer-Encoding",
+    "Upgrade",
+];
+
+// Determine whether or not the given character may appear in a token, as
+// defined in [IETF RFC 7230 section
+// 3.2.6](https://tools.ietf.org/html/rfc7230#section-3.2.6).
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+// Split the given text into its elements at each of the given delimiter,
+// with whitespace removed from around each one.  Delimiters within quoted
+// strings, as defined in [IETF RFC 7230 section
+// 3.2.6](https://tools.ietf.org/html/rfc7230#section-3.2.6), don't count.
+fn split_quoted(
+    text: &str,
+    delimiter: char,
+) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let mut element_start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if c == delimiter && !quoted {
+            elements.push(text[element_start..i].trim());
+            element_start = i + delimiter.len_utf8();
+        }
+    }
+    elements.push(text[element_start..].trim());
+    elements
+}
+
+// Remove the quotes and escaping from the given value, if it's a quoted
+// string, or return it unchanged otherwise.
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+        Some(quoted_value) => {
+            let mut unquoted_value = String::with_capacity(quoted_value.len());
+            let mut chars = quoted_value.chars();
+            while let Some(c) = chars.next() {
+                if c == '\\' {
+                    unquoted_value.extend(chars.next());
+                } else {
+                    unquoted_value.push(c);
+                }
+            }
+            unquoted_value
+        },
+        None => value.to_string(),
+    }
+}
+
+// Take as much of the given input as the given per-call body budget allows,
+// when `needed` more bytes complete a fixed-length body, and charge the
+// body bytes taken against the budget.  If the budget covers the rest of the
+// body, all the input is returned, so that anything following the body is
+// handled as usual.
+fn take_body_budget<'a>(
+    body_budget: &mut Option<usize>,
+    raw_message: &'a [u8],
+    needed: usize,
+) -> &'a [u8] {
+    match body_budget {
+        Some(budget) => {
+            let taken = raw_message.len().min(needed).min(*budget);
+            let covers_body = *budget >= needed;
+            *budget -= taken;
+            if covers_body {
+                raw_message
+            } else {
+                &raw_message[..taken]
+            }
+        },
+        None => raw_message,
+    }
+}
+
+// List the options in all `Connection` headers, normalized to lowercase.
+fn connection_options(headers: &rhymessage::MessageHeaders) -> Vec<String> {
+    headers
+        .header_tokens("Connection")
+        .into_iter()
+        .filter(|option| !option.is_empty())
+        .collect()
+}
+
+// Remove the headers named as options in the `Connection` header, along with
+// all the headers which are always hop-by-hop.
+fn strip_hop_by_hop_headers(headers: &mut rhymessage::MessageHeaders) {
+    for option in connection_options(headers) {
+        headers.remove_header(option);
+    }
+    for name in HOP_BY_HOP_HEADERS {
+        headers.remove_header(name);
+    }
+}
+
+// Remove all of the given headers.  This is used rather than replacing them
+// with new headers, so that any line limit set for them is kept.
+fn clear_headers(headers: &mut rhymessage::MessageHeaders) {
+    let names = headers
+        .headers()
+        .iter()
+        .map(|header| header.name.to_string())
+        .collect::<Vec<_>>();
+    for name in names {
+        headers.remove_header(name);
+    }
+}
+
+// Combine the values of each header which appears more than once into a
+// single header, separated by commas, at the position where the header first
+// appears.  Headers named in `never_combined` are left as separate lines, in
+// their original order.
+fn combine_repeated_headers(
+    headers: &rhymessage::MessageHeaders,
+    never_combined: &[String],
+) -> rhymessage::MessageHeaders {
+    let mut combined = headers.clone();
+    for header in headers.headers() {
+        if never_combined.iter().any(|name| header.name == name.as_str()) {
+            continue;
+        }
+        let values = header_values(&combined, &header.name);
+        if values.len() > 1 {
+            combined.set_header(&header.name, values.join(", "));
+        }
+    }
+    combined
+}
+
+// Parse the value of the `Content-Length` header, if present.  The value is
+// parsed as a `u64` first, so that a length which is valid but can't be
+// represented as a `usize` on this platform is distinguished from one which
+// is malformed.  A sign is rejected explicitly, both because `u64` parsing
+// would otherwise accept a leading `+`, and so that a negative length can be
+// told apart from other malformed values.
+fn content_length(
+    headers: &rhymessage::MessageHeaders
+) -> Result<Option<usize>, Error> {
+    headers
+        .header_value("Content-Length")
+        .map(|content_length| {
+            if content_length.starts_with(['-', '+']) {
+                return Err(Error::SignedContentLength(content_length));
+            }
+            let content_length = content_length
+                .parse::<u64>()
+                .map_err(Error::InvalidContentLength)?;
+            std::convert::TryFrom::try_from(content_length)
+                .map_err(|_| Error::ContentLengthTooLarge(content_length))
+        })
+        .transpose()
+}
+
+// Collect the values of all headers with the given name, in order, without
+// combining them.
+fn header_values<T>(
+    headers: &rhymessage::MessageHeaders,
+    name: T,
+) -> Vec<String>
 where
-    T: AsRef<[u8]>,
+    T: AsRef<str>,
 {
-    message.as_ref().windows(2).enumerate().find_map(|(i, window)| {
-        if window == b"\r\n" {
-            Some(i)
-        } else {
-            None
+    let name = name.as_ref();
+    headers
+        .headers()
+        .iter()
+        .filter(|header| header.name == name)
+        .map(|header| header.value.clone())
+        .collect()
+}
+
+// Collapse any obsolete line folding (a line break surrounded by optional
+// whitespace) in the given header value into a single space.
+fn unfold_header_value(value: &str) -> String {
+    value.split(['\r', '\n']).filter(|line| !line.is_empty()).enumerate().fold(
+        String::with_capacity(value.len()),
+        |mut unfolded, (i, line)| {
+            if i > 0 {
+                unfolded.truncate(unfolded.trim_end().len());
+                unfolded.push(' ');
+                unfolded.push_str(line.trim_start());
+            } else {
+                unfolded.push_str(line);
+            }
+            unfolded
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Generate pseudo-random bytes with a xorshift generator, so that the
+    // inputs are arbitrary but the test is repeatable.
+    fn arbitrary_bytes(
+        state: &mut u64,
+        length: usize,
+    ) -> Vec<u8> {
+        (0..length)
+            .map(|_| {
+                *state ^= *state << 13;
+                *state ^= *state >> 7;
+                *state ^= *state << 17;
+                (*state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_arbitrary_bytes_never_panics() {
+        // Seed each input with a fragment of a plausible message, so that the
+        // parsers get past the start line often enough to exercise the
+        // headers and body.
+        let prefixes: &[&[u8]] = &[
+            b"",
+            b"GET ",
+            b"GET / HTTP/1.1\r\nHost: x\r\n",
+            b"POST / HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"HTTP/1.1 ",
+            b"HTTP/1.1 200 \xce\xbb\r\nContent-Length: 99999999999999\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nFFFFFFFFFFFFFFFF",
+        ];
+        let alphabet = b" \r\n:;=\"/0aFHPT\xce\xbb";
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for prefix in prefixes {
+            for length in 0..64 {
+                for &from_alphabet in &[false, true] {
+                    let mut input = prefix.to_vec();
+                    let tail = arbitrary_bytes(&mut state, length);
+                    if from_alphabet {
+                        input.extend(tail.iter().map(|byte| {
+                            alphabet[usize::from(*byte) % alphabet.len()]
+                        }));
+                    } else {
+                        input.extend(tail);
+                    }
+                    let _ = Request::new().parse(&input);
+                    let _ = Response::new().parse(&input);
+                    let _ = Response::new().parse_at_eof(&input);
+                    let _ = chunked_body::ChunkedBody::new().decode(&input);
+                }
+            }
         }
-    })
+    }
 }
@@ -0,0 +1,137 @@
+use rhymessage::MessageHeaders;
+
+/// This enumerates the sequences of characters which may be selected to end
+/// each line of the head of a message, and of the framing of a chunked body,
+/// when parsing.  Messages are always generated with a carriage return
+/// followed by a line feed, regardless of this setting.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineTerminator {
+    /// Each line ends with a carriage return followed by a line feed, as
+    /// required by [IETF RFC 7230 section
+    /// 3](https://tools.ietf.org/html/rfc7230#section-3).  This is the
+    /// default.
+    #[default]
+    CrLf,
+
+    /// Each line ends with a line feed alone, as sent by some legacy
+    /// line-based gateways.  Any carriage return before the line feed is
+    /// treated as part of the line.
+    Lf,
+}
+
+impl LineTerminator {
+    pub(crate) fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineTerminator::CrLf => b"\r\n",
+            LineTerminator::Lf => b"\n",
+        }
+    }
+
+    // Return the offset of the first line terminator in the given input, if
+    // any.
+    pub(crate) fn find(
+        self,
+        message: &[u8],
+    ) -> Option<usize> {
+        let terminator = self.as_bytes();
+        message
+            .windows(terminator.len())
+            .position(|window| window == terminator)
+    }
+
+    // Return whether any line in the given input, which must begin at the
+    // start of a line, ends in a line feed not preceded by a carriage return.
+    // This is only possible when lines end in a line feed alone.
+    pub(crate) fn has_bare_line_feed(
+        self,
+        message: &[u8],
+    ) -> bool {
+        self == LineTerminator::Lf
+            && message.iter().enumerate().any(|(i, &byte)| {
+                byte == b'\n' && (i == 0 || message[i - 1] != b'\r')
+            })
+    }
+
+    pub(crate) fn len(self) -> usize {
+        self.as_bytes().len()
+    }
+
+    // Feed the given input into the header parser.  Since the header parser
+    // only recognizes lines ending in a carriage return and line feed, when
+    // lines end in a bare line feed, a carriage return is inserted before
+    // each line feed, and the number of bytes consumed is mapped back onto
+    // the original input.  The header parser consumes only whole lines, so
+    // the mapping always lands on a line boundary.  Any header line limit
+    // is applied as though the line ended in a carriage return and line feed.
+    pub(crate) fn parse_headers(
+        self,
+        headers: &mut MessageHeaders,
+        raw_message: &[u8],
+    ) -> Result<rhymessage::ParseResults, rhymessage::Error> {
+        match self {
+            LineTerminator::CrLf => headers.parse(raw_message),
+            LineTerminator::Lf => {
+                let mut translated = Vec::with_capacity(raw_message.len());
+                for &byte in raw_message {
+                    if byte == b'\n' {
+                        translated.push(b'\r');
+                    }
+                    translated.push(byte);
+                }
+                let mut parse_results = headers.parse(&translated)?;
+                let mut translated_consumed = 0;
+                let mut consumed = 0;
+                while translated_consumed < parse_results.consumed {
+                    translated_consumed += if raw_message[consumed] == b'\n' {
+                        2
+                    } else {
+                        1
+                    };
+                    consumed += 1;
+                }
+                parse_results.consumed = consumed;
+                Ok(parse_results)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn find_line_terminator() {
+        assert_eq!(Some(3), LineTerminator::CrLf.find(b"foo\r\nbar"));
+        assert_eq!(Some(4), LineTerminator::Lf.find(b"foo\r\nbar"));
+        assert_eq!(None, LineTerminator::CrLf.find(b"foo\nbar"));
+        assert_eq!(Some(3), LineTerminator::Lf.find(b"foo\nbar"));
+    }
+
+    #[test]
+    fn find_bare_line_feeds() {
+        assert!(LineTerminator::Lf.has_bare_line_feed(b"foo\nbar\r\n"));
+        assert!(LineTerminator::Lf.has_bare_line_feed(b"\n"));
+        assert!(!LineTerminator::Lf.has_bare_line_feed(b"foo\r\nbar\r\n"));
+        assert!(!LineTerminator::CrLf.has_bare_line_feed(b"foo\nbar\n"));
+    }
+
+    #[test]
+    fn parse_headers_with_bare_line_feeds() {
+        let mut headers = MessageHeaders::new();
+        let raw_headers = b"X-Foo: Bar\nX-Spam: Eggs\n";
+        let parse_results = LineTerminator::Lf
+            .parse_headers(&mut headers, raw_headers)
+            .unwrap();
+        assert_eq!(rhymessage::ParseStatus::Incomplete, parse_results.status);
+        assert_eq!(11, parse_results.consumed);
+        let parse_results = LineTerminator::Lf
+            .parse_headers(&mut headers, b"X-Spam: Eggs\n\nbody")
+            .unwrap();
+        assert_eq!(rhymessage::ParseStatus::Complete, parse_results.status);
+        assert_eq!(14, parse_results.consumed);
+        assert_eq!(Some("Bar"), headers.header_value("X-Foo").as_deref());
+        assert_eq!(Some("Eggs"), headers.header_value("X-Spam").as_deref());
+    }
+}
@@ -0,0 +1,150 @@
+use super::coding::split_media_type;
+
+/// This represents one of the media ranges listed in an `Accept` header, as
+/// defined in [IETF RFC 7231 section
+/// 5.3.2](https://tools.ietf.org/html/rfc7231#section-5.3.2).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaRange {
+    /// These are the parameters of the media range, such as `level=1`, as
+    /// name/value pairs in the order in which they appear.  The `q` parameter
+    /// and any extension parameters following it are not included.
+    pub parameters: Vec<(String, String)>,
+
+    /// This is the relative weight, or "quality value", of the media range,
+    /// from 0.0 to 1.0.  It defaults to 1.0 if not given.
+    pub q: f32,
+
+    /// This is the subtype of the media range, such as `html`, or `*` if any
+    /// subtype is acceptable.
+    pub subtype: String,
+
+    /// This is the type of the media range, such as `text`, or `*` if any
+    /// type is acceptable.
+    pub r#type: String,
+}
+
+impl MediaRange {
+    // Parse a single element of an `Accept` header.  `None` is returned if
+    // the element is malformed, including if it has a wildcard type with a
+    // specific subtype, or a `q` parameter which isn't a number from 0 to 1.
+    fn parse(element: &str) -> Option<Self> {
+        let (r#type, subtype, raw_parameters) = split_media_type(element)?;
+        if r#type.is_empty()
+            || subtype.is_empty()
+            || (r#type == "*" && subtype != "*")
+        {
+            return None;
+        }
+        let mut parameters = Vec::new();
+        let mut q = 1.0;
+        for (name, value) in raw_parameters {
+            if name.eq_ignore_ascii_case("q") {
+                q = value.parse::<f32>().ok()?;
+                if !(0.0..=1.0).contains(&q) {
+                    return None;
+                }
+                break;
+            }
+            parameters.push((name.to_string(), value.to_string()));
+        }
+        Some(Self {
+            parameters,
+            q,
+            subtype: subtype.to_string(),
+            r#type: r#type.to_string(),
+        })
+    }
+
+    // Rank how specific the media range is, with more specific ranges
+    // ranking higher: `*/*`, then `type/*`, then `type/subtype`, then
+    // `type/subtype` with parameters.
+    fn specificity(&self) -> usize {
+        if self.r#type == "*" {
+            0
+        } else if self.subtype == "*" {
+            1
+        } else if self.parameters.is_empty() {
+            2
+        } else {
+            3
+        }
+    }
+}
+
+// Parse the value of an `Accept` header into its media ranges, ordered by
+// precedence: more specific ranges first, then higher quality values first.
+// Ranges with equal precedence keep the order in which they appear.
+// Malformed elements are skipped.
+pub(crate) fn parse_accept(accept: &str) -> Vec<MediaRange> {
+    let mut media_ranges = accept
+        .split(',')
+        .map(str::trim)
+        .filter(|element| !element.is_empty())
+        .filter_map(MediaRange::parse)
+        .collect::<Vec<_>>();
+    media_ranges.sort_by(|a, b| {
+        b.specificity().cmp(&a.specificity()).then_with(|| {
+            b.q.partial_cmp(&a.q).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+    media_ranges
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_accept_orders_by_specificity_then_q() {
+        let media_ranges = parse_accept(
+            "*/*;q=0.8, text/*, text/html;level=1, text/plain;q=0.5, \
+             text/html",
+        );
+        let order = media_ranges
+            .iter()
+            .map(|media_range| {
+                format!("{}/{}", media_range.r#type, media_range.subtype)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["text/html", "text/html", "text/plain", "text/*", "*/*"],
+            order
+        );
+        assert_eq!(
+            vec![("level".to_string(), "1".to_string())],
+            media_ranges[0].parameters
+        );
+        assert!(media_ranges[1].parameters.is_empty());
+    }
+
+    #[test]
+    fn parse_accept_q_values() {
+        let media_ranges = parse_accept("text/plain; q=0.5; foo=bar, */*");
+        assert_eq!(2, media_ranges.len());
+        assert!((media_ranges[0].q - 0.5).abs() < f32::EPSILON);
+        assert!(media_ranges[0].parameters.is_empty());
+        assert!((media_ranges[1].q - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn parse_accept_skips_malformed_elements() {
+        let media_ranges = parse_accept(
+            "text, */html, text/plain;q=foo, text/plain;q=2, , image/png",
+        );
+        assert_eq!(
+            vec![MediaRange {
+                parameters: Vec::new(),
+                q: 1.0,
+                subtype: "png".to_string(),
+                r#type: "image".to_string(),
+            }],
+            media_ranges
+        );
+    }
+
+    #[test]
+    fn parse_accept_empty() {
+        assert!(parse_accept("").is_empty());
+    }
+}
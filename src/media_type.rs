@@ -0,0 +1,113 @@
+use super::coding::split_media_type;
+
+/// This represents a media type, such as the value of a `Content-Type`
+/// header, as defined in [IETF RFC 7231 section
+/// 3.1.1.1](https://tools.ietf.org/html/rfc7231#section-3.1.1.1).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MediaType {
+    /// These are the parameters of the media type, such as `charset=utf-8`,
+    /// as name/value pairs in the order in which they appear.
+    pub parameters: Vec<(String, String)>,
+
+    /// This is the subtype of the media type, such as `html`.
+    pub subtype: String,
+
+    /// This is the type of the media type, such as `text`.
+    pub r#type: String,
+}
+
+impl MediaType {
+    /// Return the value of the first parameter of the media type with the
+    /// given name, which is matched without regard to case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::MediaType;
+    ///
+    /// let media_type = MediaType::parse("text/html; Charset=utf-8").unwrap();
+    /// assert_eq!(Some("utf-8"), media_type.parameter("charset"));
+    /// assert_eq!(None, media_type.parameter("level"));
+    /// ```
+    #[must_use]
+    pub fn parameter<T>(
+        &self,
+        name: T,
+    ) -> Option<&str>
+    where
+        T: AsRef<str>,
+    {
+        let name = name.as_ref();
+        self.parameters.iter().find_map(|(parameter_name, value)| {
+            if parameter_name.eq_ignore_ascii_case(name) {
+                Some(value.as_str())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Parse the given text as a media type.  `None` is returned if the type
+    /// or subtype is missing.
+    #[must_use]
+    pub fn parse<T>(media_type: T) -> Option<Self>
+    where
+        T: AsRef<str>,
+    {
+        let (r#type, subtype, parameters) =
+            split_media_type(media_type.as_ref())?;
+        if r#type.is_empty() || subtype.is_empty() {
+            return None;
+        }
+        Some(Self {
+            parameters: parameters
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            subtype: subtype.to_string(),
+            r#type: r#type.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_media_type_with_parameters() {
+        assert_eq!(
+            Some(MediaType {
+                parameters: vec![
+                    ("charset".into(), "utf-8".into()),
+                    ("level".into(), "1".into()),
+                ],
+                subtype: "html".into(),
+                r#type: "text".into(),
+            }),
+            MediaType::parse("text/html ; charset=utf-8;level=1")
+        );
+    }
+
+    #[test]
+    fn parse_media_type_with_semicolon_in_quoted_parameter() {
+        assert_eq!(
+            vec![
+                ("boundary".to_string(), "\"a;b\"".to_string()),
+                ("x".to_string(), "1".to_string()),
+            ],
+            MediaType::parse("multipart/mixed; boundary=\"a;b\"; x=1")
+                .unwrap()
+                .parameters
+        );
+    }
+
+    #[test]
+    fn parse_media_type_without_subtype() {
+        assert_eq!(None, MediaType::parse("text"));
+        assert_eq!(None, MediaType::parse("text/"));
+        assert_eq!(None, MediaType::parse("/html"));
+    }
+}
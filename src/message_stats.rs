@@ -0,0 +1,22 @@
+/// This holds the number of bytes consumed by the parser for each part of the
+/// message most recently parsed, for observability.  The counts start over
+/// when the start line of the next message is parsed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MessageStats {
+    /// This is the number of bytes consumed for the body of the message,
+    /// including any chunked transfer coding framing and trailer.
+    pub body_bytes: usize,
+
+    /// This is the number of bytes consumed for the head of the message,
+    /// which is the start line, the headers, and the empty line which ends
+    /// them.
+    pub head_bytes: usize,
+}
+
+impl MessageStats {
+    /// Return the total number of bytes consumed for the message.
+    #[must_use]
+    pub fn total_bytes(&self) -> usize {
+        self.head_bytes + self.body_bytes
+    }
+}
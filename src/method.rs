@@ -0,0 +1,127 @@
+/// This enumerates the standard request methods defined in [IETF RFC 7231
+/// section 4](https://tools.ietf.org/html/rfc7231#section-4) and [IETF RFC
+/// 5789](https://tools.ietf.org/html/rfc5789).
+///
+/// When formatted with `{}`, the method is shown as it would appear in a
+/// request line, such as `GET`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Method {
+    /// The `CONNECT` method, which asks for a tunnel to the target.
+    Connect,
+
+    /// The `DELETE` method, which asks for the target to be removed.
+    Delete,
+
+    /// The `GET` method, which asks for a representation of the target.
+    Get,
+
+    /// The `HEAD` method, which is the same as `GET` except that no body is
+    /// returned.
+    Head,
+
+    /// The `OPTIONS` method, which asks about the communication options
+    /// available for the target.
+    Options,
+
+    /// The `PATCH` method, which asks for the target to be partly modified.
+    Patch,
+
+    /// The `POST` method, which asks for the body to be processed by the
+    /// target.
+    Post,
+
+    /// The `PUT` method, which asks for the target to be replaced by the body.
+    Put,
+
+    /// The `TRACE` method, which asks for the request to be echoed back.
+    Trace,
+}
+
+impl Method {
+    /// Return the method as it would appear in a request line.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Connect => "CONNECT",
+            Method::Delete => "DELETE",
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Options => "OPTIONS",
+            Method::Patch => "PATCH",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Trace => "TRACE",
+        }
+    }
+
+    /// Parse the given text as one of the standard methods.  Since methods
+    /// are case-sensitive, the text must match exactly.  `None` is returned
+    /// if the text is not a standard method.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Method;
+    ///
+    /// assert_eq!(Some(Method::Patch), Method::parse("PATCH"));
+    /// assert_eq!(None, Method::parse("patch"));
+    /// assert_eq!(None, Method::parse("BREW"));
+    /// ```
+    #[must_use]
+    pub fn parse<T>(text: T) -> Option<Self>
+    where
+        T: AsRef<str>,
+    {
+        match text.as_ref() {
+            "CONNECT" => Some(Method::Connect),
+            "DELETE" => Some(Method::Delete),
+            "GET" => Some(Method::Get),
+            "HEAD" => Some(Method::Head),
+            "OPTIONS" => Some(Method::Options),
+            "PATCH" => Some(Method::Patch),
+            "POST" => Some(Method::Post),
+            "PUT" => Some(Method::Put),
+            "TRACE" => Some(Method::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_then_format_round_trip() {
+        for method in [
+            Method::Connect,
+            Method::Delete,
+            Method::Get,
+            Method::Head,
+            Method::Options,
+            Method::Patch,
+            Method::Post,
+            Method::Put,
+            Method::Trace,
+        ] {
+            assert_eq!(Some(method), Method::parse(method.to_string()));
+        }
+    }
+
+    #[test]
+    fn parse_is_case_sensitive() {
+        assert_eq!(None, Method::parse("Get"));
+        assert_eq!(None, Method::parse(" GET"));
+    }
+}
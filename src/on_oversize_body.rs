@@ -0,0 +1,18 @@
+/// This enumerates the ways in which a parser may handle a message whose body
+/// would make the message exceed its maximum size, as set by the
+/// `max_message_size` field of [`Request`](struct.Request.html) or
+/// [`Response`](struct.Response.html).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OnOversizeBody {
+    /// The message is rejected with an
+    /// [`Error::MessageTooLong`](enum.Error.html#variant.MessageTooLong)
+    /// error.  This is the default.
+    #[default]
+    Error,
+
+    /// Only as much of the body as fits within the limit is stored, and the
+    /// rest is consumed and discarded, so that the message still completes
+    /// and the next one can be parsed.  The message is marked as having its
+    /// body truncated.
+    Truncate,
+}
@@ -0,0 +1,61 @@
+use super::OnOversizeBody;
+
+/// This holds the limits which constrain the parsing of a message, so that
+/// a single security profile can be defined once and applied consistently
+/// with [`Request::with_config`] or [`Response::with_config`].
+///
+/// [`Request::with_config`]: struct.Request.html#method.with_config
+/// [`Response::with_config`]: struct.Response.html#method.with_config
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParserConfig {
+    /// If not None, this sets a maximum size, in bytes, for each header line
+    /// of the message.  This is 1000 by default.
+    pub header_line_limit: Option<usize>,
+
+    /// If not None, this sets a maximum number of chunks permitted in a
+    /// body which uses chunked transfer coding.  This is not set by default.
+    pub max_chunk_count: Option<usize>,
+
+    /// If not None, this sets a maximum size, in bytes, for the chunk
+    /// extensions of each chunk size line in a body which uses chunked
+    /// transfer coding.  This is not set by default.
+    pub max_chunk_ext_bytes: Option<usize>,
+
+    /// If not None, this sets a maximum size, in bytes, for a body whose
+    /// content coding is reversed while parsing, when the message is set to
+    /// decode it.  This is not set by default.
+    pub max_decoded_body_size: Option<usize>,
+
+    /// This sets the maximum number of empty lines skipped before the start
+    /// line of the message.  This is zero by default.
+    pub max_leading_empty_lines: usize,
+
+    /// If not None, this sets a maximum size, in bytes, for the message as a
+    /// whole.  This is 10,000,000 by default.
+    pub max_message_size: Option<usize>,
+
+    /// This selects what happens when the body of a message would make the
+    /// message exceed [`max_message_size`](#structfield.max_message_size).
+    /// By default, the message is rejected.
+    pub on_oversize_body: OnOversizeBody,
+
+    /// If not None, this sets a maximum size, in bytes, for the start line of
+    /// the message, which is the request line of a request or the status
+    /// line of a response.  This is 1000 by default.
+    pub start_line_limit: Option<usize>,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            header_line_limit: Some(1000),
+            max_chunk_count: None,
+            max_chunk_ext_bytes: None,
+            max_decoded_body_size: None,
+            max_leading_empty_lines: 0,
+            max_message_size: Some(10_000_000),
+            on_oversize_body: OnOversizeBody::default(),
+            start_line_limit: Some(1000),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use super::{
+    chunked_body::{
+        ChunkedBody,
+        DecodeStatus,
+    },
+    error::Error,
+    LineTerminator,
+};
+
+/// This is used by a proxy to pass along a body which uses the "chunked"
+/// transfer coding without first collecting the whole body.  As each piece
+/// of the incoming body is decoded, it is encoded again as a chunk, so only
+/// the data not yet forwarded is held in memory.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate rhymuweb;
+/// use rhymuweb::Rechunker;
+///
+/// let mut rechunker = Rechunker::new();
+/// let mut output = Vec::new();
+/// assert_eq!(6, rechunker.rechunk("5\r\nHel", &mut output).unwrap());
+/// assert_eq!(b"3\r\nHel\r\n", &output[..]);
+/// assert!(!rechunker.is_complete());
+/// output.clear();
+/// assert_eq!(9, rechunker.rechunk("lo\r\n0\r\n\r\n", &mut output).unwrap());
+/// assert_eq!(b"2\r\nlo\r\n0\r\n\r\n", &output[..]);
+/// assert!(rechunker.is_complete());
+/// ```
+#[derive(Debug)]
+pub struct Rechunker {
+    chunked_body: ChunkedBody,
+    complete: bool,
+}
+
+impl Rechunker {
+    /// Indicate whether or not the whole body, including its trailer, has
+    /// been decoded and passed along.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Create a new value to pass along a chunked body.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            chunked_body: ChunkedBody::new(),
+            complete: false,
+        }
+    }
+
+    /// Decode the given input as more of a chunked body, appending to the
+    /// given output whatever chunk data was decoded, encoded again as a
+    /// chunk.  Once the body is complete, the last chunk and the trailer are
+    /// appended as well.  The number of bytes of input consumed is returned.
+    /// Any input not consumed should be given again, along with more input,
+    /// in the next call.  Once [`is_complete`](#method.is_complete) returns
+    /// `true`, any input not consumed follows the body.
+    ///
+    /// # Errors
+    ///
+    /// The same errors may be returned as for decoding a chunked body with
+    /// [`Response::parse`](struct.Response.html#method.parse).
+    pub fn rechunk<T>(
+        &mut self,
+        input: T,
+        output: &mut Vec<u8>,
+    ) -> Result<usize, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        if self.complete {
+            return Ok(0);
+        }
+        let (decode_status, consumed) =
+            self.chunked_body.decode_and_encode(input, output)?;
+        self.complete = decode_status == DecodeStatus::Complete;
+        Ok(consumed)
+    }
+
+    /// Create a new value to pass along a chunked body which uses the given
+    /// line terminator.  The body which is passed along always uses a
+    /// carriage return and line feed.
+    #[must_use]
+    pub fn with_line_terminator(line_terminator: LineTerminator) -> Self {
+        let mut rechunker = Self::new();
+        rechunker.chunked_body.line_terminator = line_terminator;
+        rechunker
+    }
+}
+
+impl Default for Rechunker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,60 +1,221 @@
 use super::{
+    challenge::{
+        parse_challenges,
+        Challenge,
+    },
     chunked_body::{
         ChunkedBody,
         DecodeStatus as ChunkedBodyDecodeStatus,
     },
+    coding::{
+        body_codings,
+        decode_body,
+        Coding,
+        has_supported_encoding,
+        is_body_encoded,
+        parse_content_type,
+    },
+    clear_headers,
+    combine_repeated_headers,
+    connection_options,
+    content_disposition::ContentDisposition,
+    content_length,
+    entity_tag::EntityTag,
     error::Error,
-    find_crlf,
-    CRLF,
+    framing::Framing,
+    header_values,
+    http_date::parse_http_date,
+    media_type::MediaType,
+    message_stats::MessageStats,
+    on_oversize_body::OnOversizeBody,
+    parser_config::ParserConfig,
+    retry_after::RetryAfter,
+    strip_hop_by_hop_headers,
+    take_body_budget,
+    BodyObserver,
+    BodyPreview,
+    LineTerminator,
+    Request,
+    TrailerMergeStrategy,
+    BODY_FRAMING_HEADERS,
+    MAX_BODY_PREALLOCATION,
 };
-use rhymessage::{
-    Header,
-    MessageHeaders,
+use rhymessage::MessageHeaders;
+use rhymuri::Uri;
+use std::{
+    io::Write,
+    time::SystemTime,
 };
-use std::io::Write;
 
-fn parse_status_line(status_line: &str) -> Result<(usize, &str), Error> {
+// Return the reason phrase which RFC 7231 (or a later RFC, for the few codes
+// it doesn't define) gives for the given status code, or an empty string if
+// the status code isn't a well-known one.
+fn canonical_reason_phrase(status_code: usize) -> &'static str {
+    match status_code {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        203 => "Non-Authoritative Information",
+        204 => "No Content",
+        205 => "Reset Content",
+        206 => "Partial Content",
+        300 => "Multiple Choices",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        305 => "Use Proxy",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        402 => "Payment Required",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        407 => "Proxy Authentication Required",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        410 => "Gone",
+        411 => "Length Required",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        415 => "Unsupported Media Type",
+        416 => "Range Not Satisfiable",
+        417 => "Expectation Failed",
+        426 => "Upgrade Required",
+        428 => "Precondition Required",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        511 => "Network Authentication Required",
+        _ => "",
+    }
+}
+
+fn parse_status_line<'a>(
+    status_line: &'a str,
+    strict_status_codes: bool,
+    accepted_protocols: &[String],
+    allow_missing_reason_phrase: bool,
+) -> Result<(&'a str, usize, &'a str), Error> {
     // Parse the protocol.
-    let protocol_delimiter = status_line.find(' ').ok_or_else(|| {
-        Error::StatusLineNoProtocolDelimiter(status_line.into())
-    })?;
-    let protocol = &status_line[..protocol_delimiter];
-    if protocol != "HTTP/1.1" {
+    let (protocol, status_line_at_status_code) =
+        status_line.split_once(' ').ok_or_else(|| {
+            Error::StatusLineNoProtocolDelimiter(status_line.into())
+        })?;
+    if !accepted_protocols
+        .iter()
+        .any(|accepted_protocol| accepted_protocol == protocol)
+    {
         return Err(Error::StatusLineProtocol(status_line.into()));
     }
 
-    // Parse the status code.
-    let status_line_at_status_code = &status_line[protocol_delimiter + 1..];
-    let status_code_delimiter =
-        status_line_at_status_code.find(' ').ok_or_else(|| {
-            Error::StatusLineNoStatusCodeDelimiter(status_line.into())
-        })?;
-    let status_code = status_line_at_status_code[..status_code_delimiter]
+    // Parse the status code and reason phrase.
+    let (status_code, reason_phrase) =
+        match status_line_at_status_code.split_once(' ') {
+            Some(split) => split,
+            None if allow_missing_reason_phrase => {
+                (status_line_at_status_code, "")
+            },
+            None => {
+                return Err(Error::StatusLineNoStatusCodeDelimiter(
+                    status_line.into(),
+                ))
+            },
+        };
+    let status_code = status_code
         .parse::<usize>()
         .map_err(Error::InvalidStatusCode)
         .and_then(|status_code| match status_code {
-            status_code if status_code < 1000 => Ok(status_code),
+            100..=599 => Ok(status_code),
+            status_code if status_code < 1000 && !strict_status_codes => {
+                Ok(status_code)
+            },
             status_code => Err(Error::StatusCodeOutOfRange(status_code)),
         })?;
+    Ok((protocol, status_code, reason_phrase))
+}
+
+// Determine whether or not the given bytes, which follow a complete response,
+// look like the start of the status line of another response: one of the
+// accepted protocol identifiers followed by a space.  Bytes which could still
+// become such a start once more input arrives count as well.
+fn starts_status_line(
+    raw_message: &[u8],
+    accepted_protocols: &[String],
+) -> bool {
+    !raw_message.is_empty()
+        && accepted_protocols.iter().any(|accepted_protocol| {
+            let prefix = [accepted_protocol.as_bytes(), b" "].concat();
+            raw_message.starts_with(&prefix) || prefix.starts_with(raw_message)
+        })
+}
 
-    // Parse the reason phrase.
-    let reason_phrase =
-        &status_line_at_status_code[status_code_delimiter + 1..];
-    Ok((status_code, reason_phrase))
+// Collect the request header names listed in a `Vary` header from the given
+// list elements, with whitespace around them removed, skipping empty elements
+// and names repeated without regard to case.  If any of them is `*`, only
+// that is kept.
+fn vary_names<'a, I>(elements: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut names: Vec<&str> = Vec::new();
+    for name in elements.into_iter().map(str::trim) {
+        if name == "*" {
+            return vec!["*"];
+        }
+        if !name.is_empty()
+            && !names.iter().any(|other| other.eq_ignore_ascii_case(name))
+        {
+            names.push(name);
+        }
+    }
+    names
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 enum ResponseState {
     ChunkedBody(ChunkedBody),
     FixedBody(usize),
     Headers,
+    #[default]
     StatusLine,
+    UntilCloseBody,
 }
 
-impl Default for ResponseState {
-    fn default() -> Self {
-        Self::StatusLine
-    }
+/// This enumerates the parts of a response which `Response::parse` can be in
+/// the middle of parsing, as reported by `Response::parse_phase`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParsePhase {
+    /// The parser is in the body of the response, which has a length given by
+    /// the `Content-Length` header, or, if the response is
+    /// [close-delimited](struct.Response.html#method.set_close_delimited),
+    /// extends until the connection is closed.
+    Body,
+
+    /// The parser is in the chunks of a response body which uses the "chunked"
+    /// transfer coding.
+    ChunkedBody,
+
+    /// The parser is in the header lines of the response.
+    Headers,
+
+    /// The parser is in the status line, at the start of the response.
+    StatusLine,
+
+    /// The parser is in the trailer of a response body which uses the
+    /// "chunked" transfer coding, after the last chunk.
+    Trailer,
 }
 
 /// This enumerates the possible non-error states `Response` can be in
@@ -69,6 +230,15 @@ pub enum ParseStatus {
     /// The user is expected to call `parse` again with more input, starting
     /// with the unparsed portion of the previous input string, and adding more
     /// to it.
+    ///
+    /// Unless the `paused` field of the results is set, the parser has
+    /// consumed all the input it can, so more input is needed before any
+    /// progress can be made.  The input not consumed is then only the start
+    /// of something which can't be decoded until more input arrives: a line
+    /// whose terminator hasn't been received yet, or, in the headers or the
+    /// trailer, the last header field received, along with the start of the
+    /// line which follows it, since a header field is only stored once the
+    /// parser can see whether the next line continues it.
     Incomplete,
 }
 
@@ -81,6 +251,23 @@ pub struct ParseResults {
     /// This is the number of bytes of input consumed by the last
     /// `Response::parse` call.
     pub consumed: usize,
+
+    /// This is the number of bytes of input given to the last
+    /// `Response::parse` call which were not consumed.  If the status is
+    /// `Complete`, these bytes follow the parsed message, and may be the
+    /// start of the next pipelined message.  After a body whose length is
+    /// given by the `Content-Length` header, only bytes which look like the
+    /// start of a status line are left unconsumed; any others are consumed
+    /// into the `trailer` field of the `Response`.
+    pub trailing: usize,
+
+    /// This indicates whether the parser stopped before consuming all the
+    /// input it could, because the number of body bytes it may consume in a
+    /// single call, set by the `body_read_budget` field of the `Response`, was
+    /// reached.  If set, `parse` may be called again with the input not
+    /// consumed, without waiting for more.  This is never set if the status
+    /// is `Complete`.
+    pub paused: bool,
 }
 
 enum ParseStatusInternal {
@@ -89,35 +276,634 @@ enum ParseStatusInternal {
     Incomplete,
 }
 
-#[derive(Debug)]
 /// This type is used to parse and generate HTTP 1.1 responses.
+///
+/// When formatted with `{:?}`, only the status code, reason phrase, number of
+/// headers, and the length and first few bytes of the body are shown.  To
+/// show everything, including the entire body, use `{:#?}` instead.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Response {
+    /// This lists the protocol identifiers which the
+    /// [`parse`](#method.parse) function accepts in the status line of a
+    /// response.  Any other identifier results in an
+    /// [`Error::StatusLineProtocol`][StatusLineProtocol] error.  By default,
+    /// this contains only `HTTP/1.1`, but other identifiers may be added for
+    /// non-standard protocols which reuse HTTP framing.
+    ///
+    /// [StatusLineProtocol]: enum.Error.html#variant.StatusLineProtocol
+    pub accepted_protocols: Vec<String>,
+
+    /// If set, the [`parse`](#method.parse) function accepts a status line
+    /// which ends right after the status code, such as `HTTP/1.1 204`, with
+    /// or without the space which would come before the reason phrase.  The
+    /// [`reason_phrase`](#structfield.reason_phrase) is left empty in that
+    /// case.  Although [IETF RFC 7230 section
+    /// 3.1.2](https://tools.ietf.org/html/rfc7230#section-3.1.2) requires the
+    /// space, some minimal servers leave it out.  This is not set by default.
+    pub allow_missing_reason_phrase: bool,
+
     /// This holds the bytes which compose the body of the response.
     pub body: Vec<u8>,
 
-    /// This holds any headers for the response.
+    body_observer: Option<BodyObserver>,
+
+    /// If not None, this caps the number of bytes of the body which the
+    /// [`parse`](#method.parse) function consumes in a single call.  Once
+    /// the cap is reached, [`parse`](#method.parse) returns
+    /// `ParseStatus::Incomplete`, even if more input is available, so that
+    /// the caller can apply backpressure, for example by flushing the body
+    /// received so far, before calling it again with the rest of the input.
+    /// For a body which uses the "chunked" transfer coding, only the chunk
+    /// data counts against the cap, not the chunk framing.  This is
+    /// independent of any limit on the overall size of the response, and is
+    /// not set by default.
+    pub body_read_budget: Option<usize>,
+
+    body_budget_remaining: Option<usize>,
+
+    body_truncated: bool,
+
+    close_delimited: bool,
+
+    /// If set, [`generate`](#method.generate) combines the values of each
+    /// header which appears more than once in the response into a single
+    /// header line, separated by commas, except for the headers named in
+    /// [`never_combined_headers`](#structfield.never_combined_headers).
+    /// Otherwise, each header is generated as a separate line, in the order
+    /// in which it was added.  This is not set by default.
+    pub combine_repeated_headers: bool,
+
+    complete: bool,
+
+    /// If set, once the body of a response has been completely parsed, the
+    /// [`parse`](#method.parse) function reverses any content coding listed
+    /// in the `Content-Encoding` header(s) of the response, in the same way
+    /// as [`coding::decode_body`](coding/fn.decode_body.html), after any
+    /// "chunked" transfer coding has been removed.  The
+    /// [`body`](#structfield.body) is then left decoded, with the
+    /// `Content-Encoding` and `Content-Length` headers updated to match.
+    /// Decoding is limited by the
+    /// [`max_decoded_body_size`](#structfield.max_decoded_body_size) field.
+    /// This is not set by default.
+    pub decode_content_encoding: bool,
+
+    framing: Framing,
+
+    head_length: usize,
+
+    /// This holds any headers for the response.  Header names are matched
+    /// without regard to case, but `rhymessage` never normalizes them, so
+    /// each name keeps the casing with which it was parsed or added, and
+    /// [`generate`](#method.generate) reproduces it exactly.  Only headers
+    /// which the parser adds or replaces itself, such as the
+    /// `Content-Length` header given to a body which used the "chunked"
+    /// transfer coding, take the casing chosen by the parser.
     pub headers: MessageHeaders,
 
+    headers_range: Option<std::ops::Range<usize>>,
+
+    leftover: Vec<u8>,
+
+    /// This selects the sequence of characters which the
+    /// [`parse`](#method.parse) function expects at the end of each line of
+    /// the response head and of the framing of a chunked body.  This is
+    /// [`LineTerminator::CrLf`](enum.LineTerminator.html#variant.CrLf) by
+    /// default, as the standard requires, but may be set to
+    /// [`LineTerminator::Lf`](enum.LineTerminator.html#variant.Lf) for peers
+    /// which only ever end lines with a bare line feed.  This does not affect
+    /// [`generate`](#method.generate).
+    pub line_terminator: LineTerminator,
+
+    /// If not None, this sets a maximum number of chunks permitted in a
+    /// response body which uses chunked transfer coding.  The
+    /// [`parse`](#method.parse) function will return an
+    /// [`Error::TooManyChunks`](enum.Error.html#variant.TooManyChunks) error
+    /// if the body contains more chunks than this.
+    pub max_chunk_count: Option<usize>,
+
+    /// If not None, this sets a maximum size, in bytes, for the chunk
+    /// extensions of each chunk size line in a response body which uses
+    /// chunked transfer coding, counting from the semicolon which begins
+    /// them.  The [`parse`](#method.parse) function will return an
+    /// [`Error::ChunkExtensionTooLong`](enum.Error.html#variant.ChunkExtensionTooLong)
+    /// error, without storing the extensions, if they are longer than this.
+    pub max_chunk_ext_bytes: Option<usize>,
+
+    /// If not None, this sets a maximum size, in bytes, for a body decoded
+    /// because the
+    /// [`decode_content_encoding`](#structfield.decode_content_encoding)
+    /// field is set, so that a small compressed body can't be expanded to
+    /// exhaust memory.  This is not set by default.
+    pub max_decoded_body_size: Option<usize>,
+
+    /// This sets the maximum number of empty lines the
+    /// [`parse`](#method.parse) function will skip before the status line of a
+    /// response.  Some peers send stray line terminators between pipelined
+    /// messages, and [IETF RFC 7230 section
+    /// 3.5](https://tools.ietf.org/html/rfc7230#section-3.5) suggests
+    /// ignoring at least one empty line received before the request line.
+    /// Any further empty lines are parsed as the status line, which fails.
+    /// This is zero by default, so no empty lines are skipped.
+    pub max_leading_empty_lines: usize,
+
+    /// If not None, this sets a maximum size, in bytes, for the response as
+    /// a whole.  The [`parse`](#method.parse) function will return a
+    /// [`Error::MessageTooLong`](enum.Error.html#variant.MessageTooLong) error
+    /// if the input exceeds this size, unless the excess is in the body and
+    /// [`on_oversize_body`](#structfield.on_oversize_body) is set to
+    /// truncate the body instead.  This is not set by default.
+    pub max_message_size: Option<usize>,
+
+    /// This lists the names of headers which [`generate`](#method.generate)
+    /// always emits as separate lines, in their original order, even if
+    /// [`combine_repeated_headers`](#structfield.combine_repeated_headers) is
+    /// set, because joining their values with commas would change their
+    /// meaning.  By default, this contains only `Set-Cookie`, as described in
+    /// [IETF RFC 6265 section
+    /// 3](https://tools.ietf.org/html/rfc6265#section-3).
+    pub never_combined_headers: Vec<String>,
+
+    /// This selects what the [`parse`](#method.parse) function does when the
+    /// body of the response would make the response exceed
+    /// [`max_message_size`](#structfield.max_message_size).  By default, the
+    /// response is rejected.  If the body is truncated instead, only as much
+    /// of it as fits is kept in the [`body`](#structfield.body) field, the
+    /// rest is discarded, and [`body_truncated`](#method.body_truncated)
+    /// reports this.  Any body observer still sees the whole body.
+    pub on_oversize_body: OnOversizeBody,
+
+    /// This is the protocol identifier in the status line of the response,
+    /// which is set by the [`parse`](#method.parse) function and used by
+    /// [`generate`](#method.generate).  This is `HTTP/1.1` by default.
+    pub protocol: std::borrow::Cow<'static, str>,
+
     /// This is the reason phrase in the response, which is a textual
     /// description associated with the numeric status code.
     pub reason_phrase: std::borrow::Cow<'static, str>,
 
+    /// If set, the [`parse`](#method.parse) function will return an
+    /// [`Error::ChunkedWithContentLength`][ChunkedWithContentLength] error if
+    /// the response has both a `Content-Length` header and the "chunked"
+    /// transfer coding.  Otherwise, as specified in [IETF RFC 7230 section
+    /// 3.3.3](https://tools.ietf.org/html/rfc7230#section-3.3.3), the
+    /// `Content-Length` header is ignored and the body is decoded as chunked.
+    /// This is not set by default.
+    ///
+    /// [ChunkedWithContentLength]: enum.Error.html#variant.ChunkedWithContentLength
+    pub reject_chunked_with_content_length: bool,
+
+    /// If set, the [`parse`](#method.parse) function will return an
+    /// [`Error::UnexpectedTrailingData`][UnexpectedTrailingData] error if
+    /// any bytes follow a body whose length is given by the `Content-Length`
+    /// header in the same input, rather than keeping them in the
+    /// [`trailer`](#structfield.trailer) field or, if they look like the
+    /// start of another response, leaving them unconsumed.  This is meant
+    /// for connections which carry only a single response, where such bytes
+    /// may be a sign of a framing bug or an injection attempt.  This is not
+    /// set by default.
+    ///
+    /// [UnexpectedTrailingData]: enum.Error.html#variant.UnexpectedTrailingData
+    pub reject_trailing_data: bool,
+
+    request_method: Option<std::borrow::Cow<'static, str>>,
+
+    request_protocol: Option<std::borrow::Cow<'static, str>>,
+
+    skipped_empty_lines: usize,
+
     state: ResponseState,
 
+    stats: MessageStats,
+
     /// This is the numeric status code in the response, which describes the
     /// result of the server's attempt to understand and satisfy the client's
     /// corresponding request.  It is defined in [IETF RFC 7231 section
     /// 6](https://tools.ietf.org/html/rfc7231#section-6).
     pub status_code: usize,
 
+    /// If not None, this sets a maximum size, in bytes, for the status line
+    /// part of the response, which is defined in [IETF RFC 7230 section
+    /// 3.1.2](https://tools.ietf.org/html/rfc7230#section-3.1.2).  The
+    /// [`parse`](#method.parse) function will return a
+    /// [`Error::StatusLineTooLong`][StatusLineTooLong] error if the input
+    /// going into the status line exceeds this size, even if the end of the
+    /// status line has not yet been received.
+    ///
+    /// [StatusLineTooLong]: enum.Error.html#variant.StatusLineTooLong
+    pub status_line_limit: Option<usize>,
+
+    status_line_range: Option<std::ops::Range<usize>>,
+
+    // This is the number of bytes of the status line which have already been
+    // scanned for the end of the line, so that they don't need to be scanned
+    // again when more input is provided.
+    status_line_scanned: usize,
+
+    /// If set, the [`parse`](#method.parse) function will return an
+    /// [`Error::StatusCodeOutOfRange`][StatusCodeOutOfRange] error if the
+    /// status code is outside the range 100&ndash;599 defined in [IETF RFC
+    /// 7231 section 6](https://tools.ietf.org/html/rfc7231#section-6).
+    /// Otherwise, any status code less than 1000 is accepted, for
+    /// compatibility with extension status codes.  This is not set by
+    /// default.
+    ///
+    /// [StatusCodeOutOfRange]: enum.Error.html#variant.StatusCodeOutOfRange
+    pub strict_status_codes: bool,
+
     /// This holds any bytes received from the server that came after
     /// the HTTP response.  They may be junk or the first bytes of the
     /// first message(s) sent by the server using a higher-level protocol.
-    /// It's not our business to judge. ¯\_(ツ)_/¯
+    /// It's not our business to judge. ¯\_(ツ)_/¯  The exception is bytes
+    /// which follow a body whose length is given by the `Content-Length`
+    /// header and look like the status line of another response, such as
+    /// one pipelined by the server: these are not consumed or kept here, but
+    /// are reported in the `trailing` count of the parse results, so that
+    /// they can be parsed as the next response.
     pub trailer: Vec<u8>,
+
+    /// This holds any headers to be placed in the trailer of the response
+    /// when it is generated with the "chunked" transfer coding.  These are
+    /// emitted after the last chunk, and their names are listed in a
+    /// `Trailer` header.  This is only used by [`generate`](#method.generate);
+    /// when a response is parsed, any headers found in its trailer are merged
+    /// into [`headers`](#structfield.headers) instead, according to
+    /// [`trailer_merge_strategy`](#structfield.trailer_merge_strategy).
+    pub trailer_headers: MessageHeaders,
+
+    /// This selects how the [`parse`](#method.parse) function merges the
+    /// headers found in the trailer of a response body which uses the
+    /// "chunked" transfer coding into [`headers`](#structfield.headers),
+    /// when a trailer header has the same name as a header already present.
+    /// This is
+    /// [`TrailerMergeStrategy::Append`](enum.TrailerMergeStrategy.html#variant.Append)
+    /// by default, which keeps both.
+    pub trailer_merge_strategy: TrailerMergeStrategy,
 }
 
 impl Response {
+    /// Create a new `400 Bad Request` response with no body, in the same way
+    /// as [`from_status`](#method.from_status).
+    #[must_use]
+    pub fn bad_request() -> Self {
+        Self::from_status(400)
+    }
+
+    /// List all the codings applied to the body of the response, as given by
+    /// its headers: first the transfer codings in the `Transfer-Encoding`
+    /// header(s), then the content codings in the `Content-Encoding`
+    /// header(s), each in the order in which they're listed, and each marked
+    /// with whether or not this crate can reverse it.  This helps decide
+    /// whether the body can be fully decoded before attempting it.  Since
+    /// the parser removes the "chunked" transfer coding once it has decoded
+    /// the body, that coding is not listed for a parsed response.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::{
+    ///     coding::CodingLayer,
+    ///     Response,
+    /// };
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Transfer-Encoding", "chunked");
+    /// response.headers.set_header("Content-Encoding", "gzip, br");
+    /// let codings = response.body_codings();
+    /// assert_eq!(3, codings.len());
+    /// assert_eq!(CodingLayer::Transfer, codings[0].layer);
+    /// assert_eq!("chunked", codings[0].name);
+    /// assert_eq!(CodingLayer::Content, codings[2].layer);
+    /// assert_eq!("br", codings[2].name);
+    /// assert!(!codings[2].supported);
+    /// ```
+    #[must_use]
+    pub fn body_codings(&self) -> Vec<Coding> {
+        body_codings(&self.headers)
+    }
+
+    /// Return a reader over the body of the response, for consuming it through
+    /// the [`Read`](https://doc.rust-lang.org/std/io/trait.Read.html) trait
+    /// without copying it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    /// use std::io::Read;
+    ///
+    /// let mut response = Response::new();
+    /// response.set_body("Hello, World!");
+    /// let mut body = String::new();
+    /// response.body_reader().read_to_string(&mut body).unwrap();
+    /// assert_eq!("Hello, World!", body);
+    /// ```
+    #[must_use]
+    pub fn body_reader(&self) -> impl std::io::Read + '_ {
+        std::io::Cursor::new(&self.body)
+    }
+
+    /// Clear the body of the response, along with the `Content-Length`,
+    /// `Transfer-Encoding`, and `Content-Encoding` headers which describe it.
+    /// All other parts of the response are left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Content-Type", "text/plain");
+    /// response.set_body("Hello, World!");
+    /// response.clear_body();
+    /// assert!(response.body.is_empty());
+    /// assert!(!response.headers.has_header("Content-Length"));
+    /// assert!(response.headers.has_header("Content-Type"));
+    /// ```
+    pub fn clear_body(&mut self) {
+        self.body.clear();
+        for name in BODY_FRAMING_HEADERS {
+            self.headers.remove_header(name);
+        }
+    }
+
+    // Clear what is left of the previous response parsed, as the status line
+    // of a new one begins, so that each response starts out with no headers,
+    // body, or trailer.
+    fn begin_message(&mut self) {
+        self.body.clear();
+        self.body_truncated = false;
+        clear_headers(&mut self.headers);
+        self.trailer.clear();
+        clear_headers(&mut self.trailer_headers);
+    }
+
+    // Determine the maximum number of bytes of the body which may be stored,
+    // if the body is to be truncated rather than rejected when it would make
+    // the response exceed its maximum size.
+    fn body_limit(&self) -> Option<usize> {
+        match (self.on_oversize_body, self.max_message_size) {
+            (OnOversizeBody::Truncate, Some(max_message_size)) => {
+                Some(max_message_size.saturating_sub(self.stats.head_bytes))
+            },
+            _ => None,
+        }
+    }
+
+    /// Indicate whether or not the body of the last response parsed was cut
+    /// off because it would have made the response exceed
+    /// [`max_message_size`](#structfield.max_message_size), which only
+    /// happens if [`on_oversize_body`](#structfield.on_oversize_body) is set
+    /// to truncate the body.
+    #[must_use]
+    pub fn body_truncated(&self) -> bool {
+        self.body_truncated
+    }
+
+    fn check_message_size(
+        &self,
+        total_bytes: usize,
+    ) -> Result<(), Error> {
+        match self.max_message_size {
+            Some(max_message_size) if total_bytes > max_message_size => {
+                Err(Error::MessageTooLong)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    // Determine whether or not the response may have a body, according to the
+    // rules of [IETF RFC 7230 section
+    // 3.3.3](https://tools.ietf.org/html/rfc7230#section-3.3.3).  These rules
+    // only apply if the method of the corresponding request is known.
+    fn body_expected(&self) -> bool {
+        match self.request_method.as_deref() {
+            Some(_) if (100..200).contains(&self.status_code) => false,
+            Some(_) if self.status_code == 204 || self.status_code == 304 => {
+                false
+            },
+            Some("HEAD") => false,
+            Some("CONNECT") => !(200..300).contains(&self.status_code),
+            _ => true,
+        }
+    }
+
+    /// Return the options listed in the `Connection` header(s) of the
+    /// response, such as `keep-alive`, `close`, or the names of other headers
+    /// which are hop-by-hop.  The options are normalized to lowercase, in the
+    /// order in which they appear.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Connection", "keep-alive, Upgrade");
+    /// assert_eq!(vec!["keep-alive", "upgrade"], response.connection_options());
+    /// ```
+    #[must_use]
+    pub fn connection_options(&self) -> Vec<String> {
+        connection_options(&self.headers)
+    }
+
+    /// Parse the `Content-Disposition` header of the response into its
+    /// disposition type, suggested file name, and parameters, or return
+    /// `None` if the response has no such header or its value is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response
+    ///     .headers
+    ///     .set_header("Content-Disposition", "attachment; filename=a.txt");
+    /// let content_disposition = response.content_disposition().unwrap();
+    /// assert_eq!("attachment", content_disposition.disposition_type);
+    /// assert_eq!(Some("a.txt"), content_disposition.filename.as_deref());
+    /// ```
+    #[must_use]
+    pub fn content_disposition(&self) -> Option<ContentDisposition> {
+        self.headers
+            .header_value("Content-Disposition")
+            .and_then(ContentDisposition::parse)
+    }
+
+    /// Return the value of the `Content-Length` header of the response as a
+    /// number, or `None` if the response has no such header.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidContentLength`][InvalidContentLength] is returned if
+    ///   the value of the `Content-Length` header is not a valid number.
+    /// * [`Error::ContentLengthTooLarge`][ContentLengthTooLarge] is returned if
+    ///   the value of the `Content-Length` header is valid, but too large to be
+    ///   represented as a `usize` on this platform.
+    /// * [`Error::SignedContentLength`][SignedContentLength] is returned if the
+    ///   value of the `Content-Length` header starts with a sign.
+    ///
+    /// [ContentLengthTooLarge]: enum.Error.html#variant.ContentLengthTooLarge
+    /// [InvalidContentLength]: enum.Error.html#variant.InvalidContentLength
+    /// [SignedContentLength]: enum.Error.html#variant.SignedContentLength
+    pub fn content_length(&self) -> Result<Option<usize>, Error> {
+        content_length(&self.headers)
+    }
+
+    /// Parse the `Content-Type` header of the response into its type, subtype,
+    /// and parameters, or return `None` if the response has no such header or
+    /// its value is not a valid media type.
+    #[must_use]
+    pub fn content_type(&self) -> Option<MediaType> {
+        parse_content_type(&self.headers)
+    }
+
+    /// Create a new `100 Continue` interim response, which a server sends to
+    /// tell a client whose request has an `Expect: 100-continue` header to go
+    /// ahead and send the body of the request, as described in [IETF RFC 7231
+    /// section 5.1.1](https://tools.ietf.org/html/rfc7231#section-5.1.1).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// assert!(matches!(
+    ///     Response::continue_interim().generate(),
+    ///     Ok(raw_response) if raw_response == b"HTTP/1.1 100 Continue\r\n\r\n"
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn continue_interim() -> Self {
+        let mut response = Self::new();
+        response.status_code = 100;
+        response.reason_phrase = canonical_reason_phrase(100).into();
+        response
+    }
+
+    /// Parse the `ETag` header of the response as an entity tag, or return
+    /// `None` if the response has no such header or its value is not a valid
+    /// entity tag.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("ETag", "W/\"xyzzy\"");
+    /// let entity_tag = response.etag().unwrap();
+    /// assert!(entity_tag.weak);
+    /// assert_eq!("xyzzy", entity_tag.tag);
+    /// ```
+    #[must_use]
+    pub fn etag(&self) -> Option<EntityTag> {
+        self.headers.header_value("ETag").and_then(EntityTag::parse)
+    }
+
+    /// Complete a [close-delimited](#method.set_close_delimited) response
+    /// whose body extends until the connection is closed, once it has been.
+    /// Any other response is already complete once [`parse`](#method.parse)
+    /// says so.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.set_close_delimited(true);
+    /// response.parse("HTTP/1.1 200 OK\r\n\r\nHello").unwrap();
+    /// assert!(!response.is_complete());
+    /// response.finalize().unwrap();
+    /// assert!(response.is_complete());
+    /// assert_eq!(b"Hello", &response.body[..]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::IncompleteMessage`][IncompleteMessage] &ndash; the
+    ///   connection was closed before the response was complete.
+    /// * The same errors may be returned as for decoding the body when
+    ///   [`decode_content_encoding`][decode_content_encoding] is set while
+    ///   parsing.
+    ///
+    /// [decode_content_encoding]: #structfield.decode_content_encoding
+    /// [IncompleteMessage]: enum.Error.html#variant.IncompleteMessage
+    pub fn finalize(&mut self) -> Result<(), Error> {
+        if let ResponseState::UntilCloseBody = self.state {
+            self.state = ResponseState::default();
+            if self.decode_content_encoding {
+                self.decode_content()?;
+            }
+            self.complete = true;
+        }
+        if self.complete {
+            Ok(())
+        } else {
+            Err(Error::IncompleteMessage)
+        }
+    }
+
+    /// Report how the parser determined the length of the body of the
+    /// response, once the headers of the response have been parsed.  This
+    /// is useful for finding out why a body was or wasn't read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::{Framing, Response};
+    ///
+    /// let mut response = Response::new();
+    /// response
+    ///     .parse("HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello")
+    ///     .unwrap();
+    /// assert_eq!(Framing::FixedLength(5), response.framing());
+    /// ```
+    #[must_use]
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Create a new response with the given status code, its canonical
+    /// reason phrase, and no body, which is the minimal valid response for
+    /// the status code.  No headers are set, not even `Content-Length`, so
+    /// that a response which has no body isn't given a spurious one.  The
+    /// other constructors of responses with no body, such as
+    /// [`not_found`](#method.not_found) and
+    /// [`internal_error`](#method.internal_error), follow the same rule.
+    /// Use [`set_body`](#method.set_body) to add a body along with its
+    /// `Content-Length` header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// assert!(matches!(
+    ///     Response::from_status(503).generate(),
+    ///     Ok(raw_response)
+    ///         if raw_response == b"HTTP/1.1 503 Service Unavailable\r\n\r\n"
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn from_status(status_code: usize) -> Self {
+        let mut response = Self::new();
+        response.status_code = status_code;
+        response.reason_phrase = canonical_reason_phrase(status_code).into();
+        response
+    }
+
     /// Produce the raw bytes form of the response, according to the rules of
     /// [IETF RFC 7320 section
     /// 3](https://tools.ietf.org/html/rfc7230#section-3):
@@ -126,8 +912,21 @@ impl Response {
     ///   numeric status code, and reason phrase.
     /// * The response header lines follow the status line.
     /// * An empty text line follows the header lines.
-    /// * The body, if any, appears last.  Its length is determined by the
-    ///   "Content-Length" header.
+    /// * The body, if any, appears last.  Its length is determined either by
+    ///   the "Content-Length" header, if present, or by the transfer coding
+    ///   technique(s) listed in the "Transfer-Encoding" header.  If the
+    ///   "chunked" transfer coding is listed, the body is emitted using the
+    ///   chunked transfer coding, as a single chunk followed by the last chunk
+    ///   and any headers in [`trailer_headers`](#structfield.trailer_headers).
+    ///
+    /// An informational (1xx) response never has a body, so for such a
+    /// response, the body and the `Content-Length`, `Transfer-Encoding`, and
+    /// `Content-Encoding` headers are left out.
+    ///
+    /// If the response is
+    /// [close-delimited](#method.set_close_delimited), the body is emitted
+    /// as is, and the `Content-Length` and `Transfer-Encoding` headers are
+    /// left out, regardless of whether they're set.
     ///
     /// # Examples
     ///
@@ -189,45 +988,682 @@ impl Response {
     ///   [`Display`](https://doc.rust-lang.org/std/fmt/trait.Display.html)
     ///   properly.
     pub fn generate(&self) -> Result<Vec<u8>, Error> {
-        let mut output = Vec::new();
-        write!(
-            &mut output,
-            "HTTP/1.1 {} {}\r\n",
-            self.status_code, self.reason_phrase
-        )
-        .map_err(Error::StringFormat)?;
-        output.append(&mut self.headers.generate().map_err(Error::Headers)?);
-        output.extend(&self.body);
-        Ok(output)
-    }
-
-    /// Create a new response value with default status code (200), reason
-    /// phrase ("OK"), and no headers or body.
-    #[must_use]
-    pub fn new() -> Self {
-        Self {
-            body: Vec::new(),
-            headers: MessageHeaders::new(),
-            reason_phrase: "OK".into(),
-            state: ResponseState::default(),
-            status_code: 200,
-            trailer: Vec::new(),
+        let mut output = self.generate_head()?;
+        if self.is_informational() {
+            return Ok(output);
         }
+        if !self.close_delimited
+            && self.headers.has_header_token("Transfer-Encoding", "chunked")
+        {
+            if !self.body.is_empty() {
+                output.append(&mut ChunkedBody::encode(&self.body)?);
+            }
+            output.append(&mut ChunkedBody::finish(&self.trailer_headers)?);
+        } else {
+            output.extend(&self.body);
+        }
+        Ok(output)
     }
 
-    /// Feed more bytes into the parser, building the response internally, and
-    /// detecting when the end of the response has been found.
-    ///
-    /// This function may be called multiple times to parse input
-    /// incrementally.  Each call returns an indication of whether or
-    /// not a message was parsed and how many input bytes were consumed.
+    /// Write the response to the given writer, using the "chunked" transfer
+    /// coding for the body, which is made up of the given chunks rather than
+    /// the [`body`](#structfield.body) field.  The head is written first,
+    /// with the `Transfer-Encoding` header set to include "chunked" and any
+    /// `Content-Length` header left out.  Each chunk is then encoded and
+    /// written as it is taken from the iterator, and finally the last chunk
+    /// and any headers in [`trailer_headers`](#structfield.trailer_headers)
+    /// are written.  Empty chunks are skipped, since an empty chunk would end
+    /// the body.  This allows a server to produce a body lazily, without
+    /// knowing its length in advance.  As with
+    /// [`generate`](#method.generate), an informational (1xx) response never
+    /// has a body, so only its head is written.
     ///
     /// # Examples
     ///
     /// ```rust
     /// # extern crate rhymuweb;
-    /// use rhymuri::Uri;
-    /// use rhymuweb::{Response, ResponseParseResults, ResponseParseStatus};
+    /// use rhymuweb::Response;
+    ///
+    /// let response = Response::new();
+    /// let chunks = vec![b"Hello, ".to_vec(), b"World!".to_vec()];
+    /// let mut output = Vec::new();
+    /// response.generate_chunked(chunks, &mut output).unwrap();
+    /// assert_eq!(
+    ///     concat!(
+    ///         "HTTP/1.1 200 OK\r\n",
+    ///         "Transfer-Encoding: chunked\r\n",
+    ///         "\r\n",
+    ///         "7\r\nHello, \r\n",
+    ///         "6\r\nWorld!\r\n",
+    ///         "0\r\n\r\n",
+    ///     )
+    ///     .as_bytes(),
+    ///     output
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The same errors may be returned as for [`generate`](#method.generate).
+    /// In addition, [`Error::Write`](enum.Error.html#variant.Write) is
+    /// returned if writing to the given writer fails.
+    pub fn generate_chunked<I, W>(
+        &self,
+        chunks: I,
+        writer: &mut W,
+    ) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+        W: std::io::Write,
+    {
+        writer
+            .write_all(&self.generate_head_with_framing(true)?)
+            .map_err(Error::Write)?;
+        if self.is_informational() {
+            return Ok(());
+        }
+        for chunk in chunks {
+            if !chunk.is_empty() {
+                writer
+                    .write_all(&ChunkedBody::encode(chunk)?)
+                    .map_err(Error::Write)?;
+            }
+        }
+        writer
+            .write_all(&ChunkedBody::finish(&self.trailer_headers)?)
+            .map_err(Error::Write)
+    }
+
+    /// Produce the raw bytes form of only the head of the response: the
+    /// status line, the header lines, and the empty line which follows them,
+    /// exactly as [`generate`](#method.generate) would produce them, but
+    /// without the body.  This allows a server to send the head right away,
+    /// and then send the body separately as it becomes available.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Transfer-Encoding", "chunked");
+    /// response.body = "Hello, World!".into();
+    /// assert!(matches!(
+    ///     response.generate_head(),
+    ///     Ok(raw_head) if raw_head == concat!(
+    ///         "HTTP/1.1 200 OK\r\n",
+    ///         "Transfer-Encoding: chunked\r\n",
+    ///         "\r\n",
+    ///     ).as_bytes()
+    /// ));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The same errors may be returned as for [`generate`](#method.generate),
+    /// except for those having to do with the body.
+    pub fn generate_head(&self) -> Result<Vec<u8>, Error> {
+        self.generate_head_with_framing(false)
+    }
+
+    /// Produce the raw bytes form of only the header lines of the response,
+    /// followed by the empty line which ends them, without the status line
+    /// or the body.  The headers are generated exactly as they are stored,
+    /// without the adjustments [`generate`](#method.generate) may make, such
+    /// as combining repeated headers or framing the body.  This is useful
+    /// for composing messages in unusual ways, such as building a trailer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Content-Type", "text/plain");
+    /// assert_eq!(
+    ///     b"Content-Type: text/plain\r\n\r\n",
+    ///     &response.generate_headers().unwrap()[..]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::Headers`](enum.Error.html#variant.Headers) &ndash; the
+    ///   headers generator is configured with a line limit constraint and one
+    ///   or more headers are too long and cannot be folded to fit within the
+    ///   constraint.
+    pub fn generate_headers(&self) -> Result<Vec<u8>, Error> {
+        self.headers.generate().map_err(Error::Headers)
+    }
+
+    // Produce the head of the response, as for `generate_head`, except that
+    // if `chunked` is set, the headers are adjusted so that the body is
+    // framed using the "chunked" transfer coding.
+    fn generate_head_with_framing(
+        &self,
+        chunked: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::new();
+        write!(
+            &mut output,
+            "{} {} {}\r\n",
+            self.protocol, self.status_code, self.reason_phrase
+        )
+        .map_err(Error::StringFormat)?;
+        let mut headers = std::borrow::Cow::Borrowed(&self.headers);
+        if self.combine_repeated_headers {
+            headers = std::borrow::Cow::Owned(combine_repeated_headers(
+                &self.headers,
+                &self.never_combined_headers,
+            ));
+        }
+        let chunked = chunked && !self.is_informational();
+        if chunked {
+            let headers = headers.to_mut();
+            headers.remove_header("Content-Length");
+            if !headers.has_header_token("Transfer-Encoding", "chunked") {
+                let transfer_encoding =
+                    match headers.header_value("Transfer-Encoding") {
+                        Some(transfer_encoding) => {
+                            format!("{transfer_encoding}, chunked")
+                        },
+                        None => "chunked".into(),
+                    };
+                headers.set_header("Transfer-Encoding", transfer_encoding);
+            }
+        } else if self.close_delimited && !self.is_informational() {
+            let headers = headers.to_mut();
+            headers.remove_header("Content-Length");
+            headers.remove_header("Transfer-Encoding");
+            if self.protocol != "HTTP/1.0"
+                && !headers.has_header_token("Connection", "close")
+            {
+                let connection = match headers.header_value("Connection") {
+                    Some(connection) => format!("{connection}, close"),
+                    None => "close".into(),
+                };
+                headers.set_header("Connection", connection);
+            }
+        }
+        if self.is_informational() {
+            for name in BODY_FRAMING_HEADERS {
+                if headers.has_header(name) {
+                    headers.to_mut().remove_header(name);
+                }
+            }
+        } else if (chunked
+            || (!self.close_delimited
+                && self
+                    .headers
+                    .has_header_token("Transfer-Encoding", "chunked")))
+            && !self.trailer_headers.headers().is_empty()
+        {
+            headers.to_mut().set_header(
+                "Trailer",
+                self.trailer_headers
+                    .headers()
+                    .iter()
+                    .map(|header| header.name.as_ref())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+        }
+        output.append(&mut headers.generate().map_err(Error::Headers)?);
+        Ok(output)
+    }
+
+    /// Return the values of all headers of the response with the given name, in
+    /// the order in which they appear.  Unlike
+    /// [`MessageHeaders::header_value`][header_value], the values are not
+    /// combined, which is important for headers such as `Set-Cookie` whose
+    /// values must not be joined with commas.
+    ///
+    /// [header_value]: https://docs.rs/rhymessage/1.3.1/rhymessage/struct.MessageHeaders.html#method.header_value
+    #[must_use]
+    pub fn header_values<T>(
+        &self,
+        name: T,
+    ) -> Vec<String>
+    where
+        T: AsRef<str>,
+    {
+        header_values(&self.headers, name)
+    }
+
+    /// Determine whether every content coding listed in the
+    /// `Content-Encoding` header(s) of the response is one which
+    /// [`coding::decode_body`](coding/fn.decode_body.html) can reverse.  This
+    /// is also true if the body has no content coding at all.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Content-Encoding", "gzip");
+    /// assert!(response.has_supported_encoding());
+    /// response.headers.set_header("Content-Encoding", "gzip, br");
+    /// assert!(!response.has_supported_encoding());
+    /// ```
+    #[must_use]
+    pub fn has_supported_encoding(&self) -> bool {
+        has_supported_encoding(&self.headers)
+    }
+
+    /// Return the range of byte offsets, relative to the start of the
+    /// response, of the header block, once it has been completely parsed.  The
+    /// header block begins just after the status line and its line terminator,
+    /// and includes the empty line which ends it.  This allows the original
+    /// header bytes to be referenced by slicing the input buffer rather than
+    /// by copying them.
+    #[must_use]
+    pub fn headers_range(&self) -> Option<std::ops::Range<usize>> {
+        self.headers_range.clone()
+    }
+
+    /// Create a new informational (1xx) interim response with the given
+    /// status code and reason phrase, and no headers or body.  Such a
+    /// response is sent before the final response to a request, as described
+    /// in [IETF RFC 7231 section
+    /// 6.2](https://tools.ietf.org/html/rfc7231#section-6.2).  Since an
+    /// interim response can't have a body, [`generate`](#method.generate)
+    /// leaves out any body or body framing headers which are added to it
+    /// later.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::interim(103, "Early Hints").unwrap();
+    /// response.headers.set_header("Link", "</style.css>; rel=preload");
+    /// response.set_body("oops");
+    /// assert!(matches!(
+    ///     response.generate(),
+    ///     Ok(raw_response) if raw_response == concat!(
+    ///         "HTTP/1.1 103 Early Hints\r\n",
+    ///         "Link: </style.css>; rel=preload\r\n",
+    ///         "\r\n",
+    ///     ).as_bytes()
+    /// ));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`Error::StatusCodeOutOfRange`][StatusCodeOutOfRange] is returned if
+    /// the given status code is not in the range 100&ndash;199.
+    ///
+    /// [StatusCodeOutOfRange]: enum.Error.html#variant.StatusCodeOutOfRange
+    pub fn interim<T>(
+        status_code: usize,
+        reason_phrase: T,
+    ) -> Result<Self, Error>
+    where
+        T: Into<std::borrow::Cow<'static, str>>,
+    {
+        if !(100..200).contains(&status_code) {
+            return Err(Error::StatusCodeOutOfRange(status_code));
+        }
+        let mut response = Self::new();
+        response.status_code = status_code;
+        response.reason_phrase = reason_phrase.into();
+        Ok(response)
+    }
+
+    /// Create a new `500 Internal Server Error` response with no body, in
+    /// the same way as
+    /// [`internal_server_error`](#method.internal_server_error), for mapping
+    /// an internal failure to a response in error-handling code.
+    #[must_use]
+    pub fn internal_error() -> Self {
+        Self::internal_server_error()
+    }
+
+    /// Create a new `500 Internal Server Error` response with no body, in
+    /// the same way as [`from_status`](#method.from_status).
+    #[must_use]
+    pub fn internal_server_error() -> Self {
+        Self::from_status(500)
+    }
+
+    /// Determine whether the `Content-Encoding` header(s) of the response
+    /// list any content coding other than `identity`, meaning the body would
+    /// need to be decoded before it can be used.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// assert!(!response.is_body_encoded());
+    /// response.headers.set_header("Content-Encoding", "identity");
+    /// assert!(!response.is_body_encoded());
+    /// response.headers.set_header("Content-Encoding", "gzip");
+    /// assert!(response.is_body_encoded());
+    /// ```
+    #[must_use]
+    pub fn is_body_encoded(&self) -> bool {
+        is_body_encoded(&self.headers)
+    }
+
+    /// Determine whether the status code of the response is in the
+    /// 4xx (Client Error) class, as defined in [IETF RFC 7231 section
+    /// 6.5](https://tools.ietf.org/html/rfc7231#section-6.5).
+    #[must_use]
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.status_code)
+    }
+
+    /// Indicate whether or not the body of the response is delimited by the
+    /// closing of the connection, as set by
+    /// [`set_close_delimited`](#method.set_close_delimited).
+    #[must_use]
+    pub fn is_close_delimited(&self) -> bool {
+        self.close_delimited
+    }
+
+    /// Indicate whether or not the last call to [`parse`](#method.parse)
+    /// completed the response.  This remains set until more input for the
+    /// next response is parsed.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Determine whether the status code of the response is in the
+    /// 1xx (Informational) class, as defined in [IETF RFC 7231 section
+    /// 6.2](https://tools.ietf.org/html/rfc7231#section-6.2).
+    #[must_use]
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.status_code)
+    }
+
+    /// Determine whether the status code of the response is in the
+    /// 3xx (Redirection) class, as defined in [IETF RFC 7231 section
+    /// 6.4](https://tools.ietf.org/html/rfc7231#section-6.4).
+    #[must_use]
+    pub fn is_redirect(&self) -> bool {
+        (300..400).contains(&self.status_code)
+    }
+
+    /// Determine whether the status code of the response is in the
+    /// 5xx (Server Error) class, as defined in [IETF RFC 7231 section
+    /// 6.6](https://tools.ietf.org/html/rfc7231#section-6.6).
+    #[must_use]
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.status_code)
+    }
+
+    /// Determine whether the status code of the response is in the
+    /// 2xx (Successful) class, as defined in [IETF RFC 7231 section
+    /// 6.3](https://tools.ietf.org/html/rfc7231#section-6.3).
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    /// Create a new response with the given status code, along with its
+    /// canonical reason phrase, and the given JSON text as the body.  The
+    /// `Content-Type` header is set to `application/json`, and the
+    /// `Content-Length` header is set to match the body.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let response = Response::json(201, r#"{"id":42}"#);
+    /// assert!(matches!(
+    ///     response.generate(),
+    ///     Ok(raw_response) if raw_response == concat!(
+    ///         "HTTP/1.1 201 Created\r\n",
+    ///         "Content-Type: application/json\r\n",
+    ///         "Content-Length: 9\r\n",
+    ///         "\r\n",
+    ///         "{\"id\":42}",
+    ///     ).as_bytes()
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn json(
+        status_code: usize,
+        body: &str,
+    ) -> Self {
+        Self::with_content(status_code, "application/json", body)
+    }
+
+    /// Parse the `Last-Modified` header of the response, which gives the date
+    /// and time at which the origin server believes the selected
+    /// representation was last modified, as described in [IETF RFC 7232
+    /// section 2.2](https://tools.ietf.org/html/rfc7232#section-2.2).  The
+    /// date may be in the preferred IMF-fixdate format or either of the
+    /// obsolete formats described in [IETF RFC 7231 section
+    /// 7.1.1.1](https://tools.ietf.org/html/rfc7231#section-7.1.1.1).
+    /// `None` is returned if the response has no such header or its value
+    /// isn't a valid date.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let mut response = Response::new();
+    /// response
+    ///     .headers
+    ///     .set_header("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT");
+    /// assert_eq!(
+    ///     Some(SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777)),
+    ///     response.last_modified()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.headers
+            .header_value("Last-Modified")
+            .and_then(|value| parse_http_date(&value))
+    }
+
+    /// Parse the `Location` header of the response as a URI, resolving it
+    /// against the given base URI, if any, in case it's a relative reference,
+    /// as allowed by [IETF RFC 7231 section
+    /// 7.1.2](https://tools.ietf.org/html/rfc7231#section-7.1.2).  The base
+    /// is normally the target URI of the request.  `None` is returned if the
+    /// response has no `Location` header, unless it's a redirect (301, 302,
+    /// 303, 307, or 308), which requires one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuri;
+    /// # extern crate rhymuweb;
+    /// use rhymuri::Uri;
+    /// use rhymuweb::Response;
+    ///
+    /// # fn main() -> Result<(), rhymuweb::Error> {
+    /// let mut response = Response::new();
+    /// response.status_code = 302;
+    /// response.headers.set_header("Location", "../bar?baz");
+    /// let base = Uri::parse("http://www.example.com/foo/index.html")?;
+    /// assert!(matches!(
+    ///     response.location(Some(&base)),
+    ///     Some(Ok(location))
+    ///         if location.to_string() == "http://www.example.com/bar?baz"
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LocationUriInvalid`][LocationUriInvalid] is returned if the
+    ///   value of the `Location` header is not a valid URI.
+    /// * [`Error::MissingLocationHeader`][MissingLocationHeader] is returned if
+    ///   the response is a redirect but has no `Location` header.
+    ///
+    /// [LocationUriInvalid]: enum.Error.html#variant.LocationUriInvalid
+    /// [MissingLocationHeader]: enum.Error.html#variant.MissingLocationHeader
+    #[must_use]
+    pub fn location(
+        &self,
+        base: Option<&Uri>,
+    ) -> Option<Result<Uri, Error>> {
+        match self.headers.header_value("Location") {
+            Some(location) => Some(
+                Uri::parse(location.trim())
+                    .map(|location| match base {
+                        Some(base) => base.resolve(&location),
+                        None => location,
+                    })
+                    .map_err(Error::LocationUriInvalid),
+            ),
+            None if matches!(self.status_code, 301 | 302 | 303 | 307 | 308) => {
+                Some(Err(Error::MissingLocationHeader))
+            },
+            None => None,
+        }
+    }
+
+    /// Create a new `405 Method Not Allowed` response with no body, for
+    /// rejecting a request whose method the target resource doesn't support.
+    /// As required by [IETF RFC 7231 section
+    /// 6.5.5](https://tools.ietf.org/html/rfc7231#section-6.5.5), the `Allow`
+    /// header lists the given methods which are supported.  As with
+    /// [`from_status`](#method.from_status), no `Content-Length` header is
+    /// set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let response = Response::method_not_allowed(&["GET", "HEAD"]);
+    /// assert!(matches!(
+    ///     response.generate(),
+    ///     Ok(raw_response) if raw_response == concat!(
+    ///         "HTTP/1.1 405 Method Not Allowed\r\n",
+    ///         "Allow: GET, HEAD\r\n",
+    ///         "\r\n",
+    ///     ).as_bytes()
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn method_not_allowed(allowed: &[&str]) -> Self {
+        let mut response = Self::from_status(405);
+        response.headers.set_header("Allow", allowed.join(", "));
+        response
+    }
+
+    /// Create a new response value with default status code (200), reason
+    /// phrase ("OK"), no headers or body, and default limit constraints.  As
+    /// for a request, the header line limit is 1000 bytes by default, and may
+    /// be changed with [`set_header_line_limit`](#method.set_header_line_limit).
+    #[must_use]
+    pub fn new() -> Self {
+        let mut response = Self {
+            accepted_protocols: vec!["HTTP/1.1".into()],
+            allow_missing_reason_phrase: false,
+            body: Vec::new(),
+            body_budget_remaining: None,
+            body_observer: None,
+            body_read_budget: None,
+            body_truncated: false,
+            close_delimited: false,
+            combine_repeated_headers: false,
+            complete: false,
+            decode_content_encoding: false,
+            framing: Framing::default(),
+            head_length: 0,
+            headers: MessageHeaders::new(),
+            headers_range: None,
+            leftover: Vec::new(),
+            line_terminator: LineTerminator::default(),
+            max_chunk_count: None,
+            max_chunk_ext_bytes: None,
+            max_decoded_body_size: None,
+            max_leading_empty_lines: 0,
+            max_message_size: None,
+            never_combined_headers: vec!["Set-Cookie".into()],
+            on_oversize_body: OnOversizeBody::default(),
+            protocol: "HTTP/1.1".into(),
+            reason_phrase: "OK".into(),
+            reject_chunked_with_content_length: false,
+            reject_trailing_data: false,
+            request_method: None,
+            request_protocol: None,
+            skipped_empty_lines: 0,
+            state: ResponseState::default(),
+            stats: MessageStats::default(),
+            status_code: 200,
+            status_line_limit: Some(1000),
+            status_line_range: None,
+            status_line_scanned: 0,
+            strict_status_codes: false,
+            trailer: Vec::new(),
+            trailer_headers: MessageHeaders::new(),
+            trailer_merge_strategy: TrailerMergeStrategy::default(),
+        };
+        response.headers.set_line_limit(Some(1000));
+        response
+    }
+
+    /// Create a new `404 Not Found` response with no body, in the same way
+    /// as [`from_status`](#method.from_status).
+    #[must_use]
+    pub fn not_found() -> Self {
+        Self::from_status(404)
+    }
+
+    // Reverse any content coding of the body, once it has been completely
+    // parsed.
+    fn decode_content(&mut self) -> Result<(), Error> {
+        if !self.body.is_empty() && is_body_encoded(&self.headers) {
+            self.body = decode_body(
+                &mut self.headers,
+                &self.body,
+                self.max_decoded_body_size,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn observe_body(
+        &mut self,
+        segment: &[u8],
+    ) {
+        if segment.is_empty() {
+            return;
+        }
+        if let Some(body_observer) = &mut self.body_observer {
+            body_observer(segment);
+        }
+    }
+
+    /// Feed more bytes into the parser, building the response internally, and
+    /// detecting when the end of the response has been found.
+    ///
+    /// This function may be called multiple times to parse input
+    /// incrementally.  Each call returns an indication of whether or
+    /// not a message was parsed and how many input bytes were consumed.
+    /// Once a response is complete, parsing more input starts the next one,
+    /// and the status line, headers, body, and trailer of the previous response are replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuri::Uri;
+    /// use rhymuweb::{Response, ResponseParseResults, ResponseParseStatus};
     ///
     /// # fn main() -> Result<(), rhymessage::Error> {
     /// let raw_response = concat!(
@@ -257,7 +1693,8 @@ impl Response {
     ///     response.parse(raw_response),
     ///     Ok(ResponseParseResults{
     ///         status: ResponseParseStatus::Complete,
-    ///         consumed
+    ///         consumed,
+    ///         ..
     ///     }) if consumed == raw_response.len()
     /// ));
     /// assert_eq!(200, response.status_code);
@@ -302,6 +1739,9 @@ impl Response {
     ///
     /// # Errors
     ///
+    /// * [`Error::StatusLineTooLong`][StatusLineTooLong] &ndash; the status
+    ///   line in the response exceeds the maximum size constraint set in the
+    ///   [`status_line_limit`][status_line_limit] field
     /// * [`Error::StatusLineNotValidText`][StatusLineNotValidText] &ndash; the
     ///   status line contained bytes which could not be decoded as valid UTF-8
     ///   text
@@ -310,33 +1750,71 @@ impl Response {
     ///   parsed because no space character delimiting the protocol identifier
     ///   from the numeric status code could be found
     /// * [`Error::StatusLineProtocol`][StatusLineProtocol] &ndash; the protocol
-    ///   identifier part of the status line is either missing or does not match
-    ///   "HTTP/1.1"
+    ///   identifier part of the status line is either missing or is not one of
+    ///   the identifiers listed in the
+    ///   [`accepted_protocols`](#structfield.accepted_protocols) field
     /// * [`Error::StatusLineNoStatusCodeDelimiter`][StatusLineNoStatusCodeDelimiter]
     ///   &ndash; the numeric status code part of the status line could not be
     ///   parsed because no space character delimiting the numeric status code
-    ///   from the reason phrase could be found
+    ///   from the reason phrase could be found, and the
+    ///   [`allow_missing_reason_phrase`](#structfield.allow_missing_reason_phrase)
+    ///   field is not set
     /// * [`Error::InvalidStatusCode`][InvalidStatusCode] &ndash; the value of
     ///   the numeric status code in the status line could not be parsed
     /// * [`Error::StatusCodeOutOfRange`][StatusCodeOutOfRange] &ndash; the
     ///   value of the numeric status code in the status line is greater than
-    ///   999, the maximum permitted value
+    ///   999, the maximum permitted value, or is outside the range
+    ///   100&ndash;599 and the
+    ///   [`strict_status_codes`](#structfield.strict_status_codes) field is set
     /// * [`Error::Headers`][Headers] &ndash; an error occurred parsing the
     ///   response headers
+    /// * [`Error::MessageTooLong`][MessageTooLong] &ndash; the response
+    ///   exceeds the maximum size constraint set in the
+    ///   [`max_message_size`][max_message_size] field
     /// * [`Error::InvalidContentLength`][InvalidContentLength] &ndash; the
     ///   value of the "Content-Length" header of the response could not be
     ///   parsed
+    /// * [`Error::ContentLengthTooLarge`][ContentLengthTooLarge] &ndash; the
+    ///   value of the "Content-Length" header of the response is too large to
+    ///   be represented as a `usize` on this platform
+    /// * [`Error::SignedContentLength`][SignedContentLength] &ndash; the value
+    ///   of the "Content-Length" header of the response starts with a sign
+    /// * [`Error::ChunkedWithContentLength`][ChunkedWithContentLength] &ndash;
+    ///   the response has both a "Content-Length" header and the "chunked"
+    ///   transfer coding, and the
+    ///   [`reject_chunked_with_content_length`][reject] field is set
     /// * [`Error::ChunkSizeLineNotValidText`][ChunkSizeLineNotValidText]
     ///   &ndash; a chunk size line contained bytes which could not be decoded
     ///   as valid UTF-8 text
+    /// * [`Error::ChunkExtensionTooLong`][ChunkExtensionTooLong] &ndash; the
+    ///   chunk extensions in a chunk size line are longer than permitted by
+    ///   the [`max_chunk_ext_bytes`][max_chunk_ext_bytes] field
+    /// * [`Error::InvalidChunkExtension`][InvalidChunkExtension] &ndash; the
+    ///   chunk extensions in a chunk size line could not be parsed
     /// * [`Error::InvalidChunkSize`][InvalidChunkSize] &ndash; the value of a
     ///   chunk size could not be parsed
     /// * [`Error::InvalidChunkTerminator`][InvalidChunkTerminator] &ndash;
     ///   extra junk was found at the end of a chunk rather than carriage-return
     ///   and line-feed, which are required
+    /// * [`Error::TooManyChunks`][TooManyChunks] &ndash; the chunked-encoded
+    ///   body contains more chunks than permitted by the
+    ///   [`max_chunk_count`][max_chunk_count] field
     /// * [`Error::Trailer`][Trailer] &ndash; an error occurred parsing the
     ///   headers contained in the trailer for the chunked-encoded body
+    /// * [`Error::UnexpectedTrailingData`][UnexpectedTrailingData] &ndash;
+    ///   bytes followed a body whose length is given by the "Content-Length"
+    ///   header, and the [`reject_trailing_data`][reject_trailing_data] field
+    ///   is set
+    /// * [`Error::BadContentEncoding`][BadContentEncoding] &ndash; the
+    ///   [`decode_content_encoding`][decode_content_encoding] field is set,
+    ///   and an error occurred reversing the content coding of the body
+    /// * [`Error::DecodedBodyTooLarge`][DecodedBodyTooLarge] &ndash; the
+    ///   [`decode_content_encoding`][decode_content_encoding] field is set,
+    ///   and the decoded body would be larger than permitted by the
+    ///   [`max_decoded_body_size`][max_decoded_body_size] field
     ///
+    /// [StatusLineTooLong]: enum.Error.html#variant.StatusLineTooLong
+    /// [status_line_limit]: #structfield.status_line_limit
     /// [StatusLineNotValidText]: enum.Error.html#variant.StatusLineNotValidText
     /// [StatusLineNoProtocolDelimiter]:
     /// enum.Error.html#variant.StatusLineNoProtocolDelimiter
@@ -346,12 +1824,29 @@ impl Response {
     /// [InvalidStatusCode]: enum.Error.html#variant.InvalidStatusCode
     /// [StatusCodeOutOfRange]: enum.Error.html#variant.StatusCodeOutOfRange
     /// [Headers]: enum.Error.html#variant.Headers
+    /// [MessageTooLong]: enum.Error.html#variant.MessageTooLong
+    /// [max_message_size]: #structfield.max_message_size
     /// [InvalidContentLength]: enum.Error.html#variant.InvalidContentLength
+    /// [ContentLengthTooLarge]: enum.Error.html#variant.ContentLengthTooLarge
+    /// [SignedContentLength]: enum.Error.html#variant.SignedContentLength
+    /// [ChunkedWithContentLength]: enum.Error.html#variant.ChunkedWithContentLength
+    /// [reject]: #structfield.reject_chunked_with_content_length
     /// [ChunkSizeLineNotValidText]:
     /// enum.Error.html#variant.ChunkSizeLineNotValidText
+    /// [ChunkExtensionTooLong]: enum.Error.html#variant.ChunkExtensionTooLong
+    /// [max_chunk_ext_bytes]: #structfield.max_chunk_ext_bytes
+    /// [InvalidChunkExtension]: enum.Error.html#variant.InvalidChunkExtension
     /// [InvalidChunkSize]: enum.Error.html#variant.InvalidChunkSize
     /// [InvalidChunkTerminator]: enum.Error.html#variant.InvalidChunkTerminator
+    /// [TooManyChunks]: enum.Error.html#variant.TooManyChunks
+    /// [max_chunk_count]: #structfield.max_chunk_count
     /// [Trailer]: enum.Error.html#variant.Trailer
+    /// [UnexpectedTrailingData]: enum.Error.html#variant.UnexpectedTrailingData
+    /// [reject_trailing_data]: #structfield.reject_trailing_data
+    /// [BadContentEncoding]: enum.Error.html#variant.BadContentEncoding
+    /// [decode_content_encoding]: #structfield.decode_content_encoding
+    /// [DecodedBodyTooLarge]: enum.Error.html#variant.DecodedBodyTooLarge
+    /// [max_decoded_body_size]: #structfield.max_decoded_body_size
     pub fn parse<T>(
         &mut self,
         raw_message: T,
@@ -361,6 +1856,7 @@ impl Response {
     {
         let raw_message = raw_message.as_ref();
         let mut total_consumed = 0;
+        self.body_budget_remaining = self.body_read_budget;
         loop {
             let raw_message_remainder = &raw_message[total_consumed..];
             let state = std::mem::take(&mut self.state);
@@ -375,7 +1871,7 @@ impl Response {
                         .parse_message_for_fixed_body(
                             raw_message_remainder,
                             content_length,
-                        );
+                        )?;
                     (
                         parse_status,
                         ResponseState::FixedBody(content_length),
@@ -388,58 +1884,297 @@ impl Response {
                 ResponseState::StatusLine => {
                     self.parse_message_for_status_line(raw_message_remainder)?
                 },
+                ResponseState::UntilCloseBody => {
+                    let consumed = self.parse_message_for_until_close_body(
+                        raw_message_remainder,
+                    )?;
+                    (
+                        ParseStatusInternal::Incomplete,
+                        ResponseState::UntilCloseBody,
+                        consumed,
+                    )
+                },
             };
             self.state = state;
             total_consumed += consumed;
+            if consumed > 0 {
+                self.complete = false;
+            }
             match parse_status {
                 ParseStatusInternal::CompletePart => (),
                 ParseStatusInternal::CompleteWhole => {
+                    self.state = ResponseState::default();
+                    if self.decode_content_encoding {
+                        self.decode_content()?;
+                    }
+                    self.complete = true;
                     return Ok(ParseResults {
                         status: ParseStatus::Complete,
                         consumed: total_consumed,
+                        trailing: raw_message.len() - total_consumed,
+                        paused: false,
                     });
                 },
                 ParseStatusInternal::Incomplete => {
                     return Ok(ParseResults {
                         status: ParseStatus::Incomplete,
                         consumed: total_consumed,
+                        trailing: raw_message.len() - total_consumed,
+                        paused: self.body_budget_remaining == Some(0)
+                            && total_consumed < raw_message.len(),
                     });
                 },
-            };
+            }
         }
     }
 
-    fn parse_message_for_chunked_body(
+    /// Feed the last bytes received before the server closed the connection
+    /// into the parser, in the same way as [`parse`](#method.parse).  Some
+    /// servers close the connection after the last chunk of a chunked body,
+    /// or after its trailer, without sending the empty line which should end
+    /// the response.  If the parser is in the trailer when the input runs out,
+    /// the missing line terminators are supplied so that the response can be
+    /// completed rather than waiting for input which will never arrive.  A
+    /// [close-delimited](#method.set_close_delimited) body is completed by
+    /// the end of the input, as with [`finalize`](#method.finalize).  In
+    /// any other part of the response, the input is parsed as usual, so an
+    /// incomplete response is still reported as incomplete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::{
+    ///     Response,
+    ///     ResponseParseResults,
+    ///     ResponseParseStatus,
+    /// };
+    ///
+    /// let raw_response = concat!(
+    ///     "HTTP/1.1 200 OK\r\n",
+    ///     "Transfer-Encoding: chunked\r\n",
+    ///     "\r\n",
+    ///     "5\r\nHello\r\n",
+    ///     "0\r\n",
+    /// );
+    /// let mut response = Response::new();
+    /// assert!(matches!(
+    ///     response.parse_at_eof(raw_response),
+    ///     Ok(ResponseParseResults {
+    ///         status: ResponseParseStatus::Complete,
+    ///         ..
+    ///     })
+    /// ));
+    /// assert_eq!(b"Hello", &response.body[..]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The same errors may be returned as for [`parse`](#method.parse).
+    pub fn parse_at_eof<T>(
+        &mut self,
+        raw_message: T,
+    ) -> Result<ParseResults, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let raw_message = raw_message.as_ref();
+        let parse_results = self.parse(raw_message)?;
+        if let ResponseState::UntilCloseBody = self.state {
+            if parse_results.consumed < raw_message.len() {
+                return Ok(parse_results);
+            }
+            self.finalize()?;
+            return Ok(ParseResults {
+                status: ParseStatus::Complete,
+                consumed: raw_message.len(),
+                trailing: 0,
+                paused: false,
+            });
+        }
+        if parse_results.status == ParseStatus::Complete
+            || self.parse_phase() != ParsePhase::Trailer
+        {
+            return Ok(parse_results);
+        }
+        let terminator = self.line_terminator.as_bytes();
+        let mut remainder = raw_message[parse_results.consumed..].to_vec();
+        if !remainder.is_empty() && !remainder.ends_with(terminator) {
+            remainder.extend(terminator);
+        }
+        remainder.extend(terminator);
+        match self.parse(&remainder)?.status {
+            ParseStatus::Complete => Ok(ParseResults {
+                status: ParseStatus::Complete,
+                consumed: raw_message.len(),
+                trailing: 0,
+                paused: false,
+            }),
+            ParseStatus::Incomplete => Ok(parse_results),
+        }
+    }
+
+    /// Feed the given chunks of bytes, in order, to the parser, keeping any
+    /// bytes not yet consumed in an internal buffer, so that the chunks may
+    /// be split at arbitrary boundaries.  Chunks are taken from the iterator
+    /// only until the response is complete, so any remaining chunks may be fed
+    /// into the next call.  Any bytes left over in the internal buffer after
+    /// the response is complete are parsed first on the next call, or may be
+    /// retrieved with [`take_leftover`](#method.take_leftover).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::{
+    ///     Response,
+    ///     ResponseParseStatus,
+    /// };
+    ///
+    /// let mut response = Response::new();
+    /// let chunks = vec![
+    ///     b"HTTP/1.1 200 OK\r\nContent-Le".to_vec(),
+    ///     b"ngth: 5\r\n\r\nHel".to_vec(),
+    ///     b"lo".to_vec(),
+    /// ];
+    /// assert!(matches!(
+    ///     response.parse_chunks(chunks),
+    ///     Ok(ResponseParseStatus::Complete)
+    /// ));
+    /// assert_eq!(b"Hello", &response.body[..]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The same errors may be returned as for [`parse`](#method.parse).
+    pub fn parse_chunks<I>(
+        &mut self,
+        chunks: I,
+    ) -> Result<ParseStatus, Error>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        let mut chunks = chunks.into_iter();
+        let mut have_new_input = !self.leftover.is_empty();
+        loop {
+            if have_new_input {
+                let mut leftover = std::mem::take(&mut self.leftover);
+                let parse_results = self.parse(&leftover)?;
+                leftover.drain(..parse_results.consumed);
+                self.leftover = leftover;
+                if parse_results.status == ParseStatus::Complete {
+                    return Ok(ParseStatus::Complete);
+                }
+            }
+            match chunks.next() {
+                Some(chunk) => {
+                    self.leftover.extend(chunk);
+                    have_new_input = true;
+                },
+                None => return Ok(ParseStatus::Incomplete),
+            }
+        }
+    }
+
+    /// Feed more bytes into the parser, building the response internally, in
+    /// the same way as [`parse`](#method.parse), but also applying the
+    /// message framing rules which depend on the request to which the
+    /// response corresponds, as specified in [IETF RFC 7230 section
+    /// 3.3.3](https://tools.ietf.org/html/rfc7230#section-3.3.3):
+    ///
+    /// * Responses to `HEAD` requests never have a body.
+    /// * Successful (2xx) responses to `CONNECT` requests never have a body;
+    ///   any bytes following the headers belong to the tunnel and are left
+    ///   unconsumed.
+    /// * Informational (1xx), `204 No Content`, and `304 Not Modified`
+    ///   responses never have a body.
+    /// * If the request used HTTP/1.0, which has no other way to delimit a
+    ///   body, a response with neither a `Content-Length` header nor the
+    ///   "chunked" transfer coding has a body which extends until the server
+    ///   closes the connection, as if the response were
+    ///   [close-delimited](#method.set_close_delimited).  Call
+    ///   [`finalize`](#method.finalize) once the connection is closed.
+    ///
+    /// These rules apply only to the input given in this call, so the same
+    /// request should be given for every call made to parse the response.
+    /// Later calls to [`parse`](#method.parse) don't apply them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::{
+    ///     Request,
+    ///     Response,
+    ///     ResponseParseResults,
+    ///     ResponseParseStatus,
+    /// };
+    ///
+    /// let mut request = Request::new();
+    /// request.method = "HEAD".into();
+    /// let raw_response = concat!(
+    ///     "HTTP/1.1 200 OK\r\n",
+    ///     "Content-Length: 51\r\n",
+    ///     "\r\n",
+    /// );
+    /// let mut response = Response::new();
+    /// assert!(matches!(
+    ///     response.parse_for_request(&request, raw_response),
+    ///     Ok(ResponseParseResults{
+    ///         status: ResponseParseStatus::Complete,
+    ///         consumed,
+    ///         ..
+    ///     }) if consumed == raw_response.len()
+    /// ));
+    /// assert!(response.body.is_empty());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The same errors may be returned as for [`parse`](#method.parse).
+    pub fn parse_for_request<T>(
+        &mut self,
+        request: &Request,
+        raw_message: T,
+    ) -> Result<ParseResults, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.request_method = Some(request.method.clone());
+        self.request_protocol = Some(request.protocol.clone());
+        let parse_results = self.parse(raw_message);
+        self.request_method = None;
+        self.request_protocol = None;
+        parse_results
+    }
+
+    fn parse_message_for_chunked_body(
         &mut self,
         raw_message: &[u8],
         mut chunked_body: ChunkedBody,
     ) -> Result<(ParseStatusInternal, ResponseState, usize), Error> {
-        match chunked_body.decode(raw_message)? {
+        let decoded_before = chunked_body.buffer.len();
+        chunked_body.data_budget = self.body_budget_remaining;
+        let decode_results = chunked_body.decode(raw_message)?;
+        self.body_budget_remaining = chunked_body.data_budget;
+        self.observe_body(&chunked_body.buffer[decoded_before..]);
+        self.stats.body_bytes += decode_results.1;
+        // The decoded body is cut back to the limit after each call, so it
+        // never grows past the limit by more than the input given at once.
+        match self.body_limit() {
+            Some(body_limit) if chunked_body.buffer.len() > body_limit => {
+                chunked_body.buffer.truncate(body_limit);
+                self.body_truncated = true;
+            },
+            Some(_) => (),
+            None => self.check_message_size(self.stats.total_bytes())?,
+        }
+        match decode_results {
             (ChunkedBodyDecodeStatus::Complete, consumed) => {
-                self.body = std::mem::take(&mut chunked_body.buffer);
-                for header in chunked_body.trailer {
-                    self.headers.add_header(header);
-                }
-
-                // Now that we've decoded the chunked body, we should remove
-                // the "chunked" token from the `Transfer-Encoding` header,
-                // and add a `Content-Length` header.
-                let mut transfer_encodings =
-                    self.headers.header_tokens("Transfer-Encoding");
-                transfer_encodings.pop();
-                if transfer_encodings.is_empty() {
-                    self.headers.remove_header("Transfer-Encoding");
-                } else {
-                    self.headers.set_header(
-                        "Transfer-Encoding",
-                        transfer_encodings.join(" "),
-                    );
-                }
-                self.headers.add_header(Header {
-                    name: "Content-Length".into(),
-                    value: self.body.len().to_string(),
-                });
-                self.headers.remove_header("Trailer");
+                self.body = chunked_body.finish_decoding(
+                    &mut self.headers,
+                    self.trailer_merge_strategy,
+                );
                 Ok((
                     ParseStatusInternal::CompleteWhole,
                     ResponseState::default(),
@@ -458,56 +2193,124 @@ impl Response {
         &mut self,
         raw_message: &[u8],
         content_length: usize,
-    ) -> (ParseStatusInternal, usize) {
-        let needed = content_length - self.body.len();
-        (
-            if raw_message.len() >= needed {
-                self.body.extend(&raw_message[..needed]);
-                self.trailer.extend(&raw_message[needed..]);
-                ParseStatusInternal::CompleteWhole
-            } else {
-                self.body.extend(raw_message);
-                ParseStatusInternal::Incomplete
-            },
-            raw_message.len(),
-        )
+    ) -> Result<(ParseStatusInternal, usize), Error> {
+        let needed = content_length - self.stats.body_bytes;
+        let raw_message = take_body_budget(
+            &mut self.body_budget_remaining,
+            raw_message,
+            needed,
+        );
+        if self.reject_trailing_data && raw_message.len() > needed {
+            return Err(Error::UnexpectedTrailingData(
+                raw_message.len() - needed,
+            ));
+        }
+        if raw_message.len() >= needed {
+            self.stats.body_bytes += needed;
+            self.observe_body(&raw_message[..needed]);
+            self.store_body(&raw_message[..needed]);
+
+            // Leave any bytes which look like the start of the next response
+            // unconsumed, so they can be parsed as such, rather than taking
+            // them into the trailer.
+            let extra = &raw_message[needed..];
+            if starts_status_line(extra, &self.accepted_protocols) {
+                return Ok((ParseStatusInternal::CompleteWhole, needed));
+            }
+            self.trailer.extend(extra);
+            Ok((ParseStatusInternal::CompleteWhole, raw_message.len()))
+        } else {
+            self.stats.body_bytes += raw_message.len();
+            self.observe_body(raw_message);
+            self.store_body(raw_message);
+            Ok((ParseStatusInternal::Incomplete, raw_message.len()))
+        }
     }
 
     fn parse_message_for_headers(
         &mut self,
         raw_message: &[u8],
     ) -> Result<(ParseStatusInternal, ResponseState, usize), Error> {
-        let parse_results =
-            self.headers.parse(raw_message).map_err(Error::Headers)?;
+        let parse_results = self
+            .line_terminator
+            .parse_headers(&mut self.headers, raw_message)
+            .map_err(Error::Headers)?;
+        self.stats.head_bytes += parse_results.consumed;
+        self.check_message_size(self.stats.total_bytes())?;
+        self.head_length += parse_results.consumed;
         match parse_results.status {
             rhymessage::ParseStatus::Complete => {
-                if let Some(content_length) =
-                    self.headers.header_value("Content-Length")
-                {
-                    let content_length = content_length
-                        .parse::<usize>()
-                        .map_err(Error::InvalidContentLength)?;
-                    self.body.reserve(content_length);
+                self.headers_range =
+                    self.status_line_range.as_ref().map(|status_line_range| {
+                        status_line_range.end + self.line_terminator.len()
+                            ..self.head_length
+                    });
+                if !self.body_expected() {
+                    self.framing = Framing::None;
                     Ok((
-                        ParseStatusInternal::CompletePart,
-                        ResponseState::FixedBody(content_length),
+                        ParseStatusInternal::CompleteWhole,
+                        ResponseState::Headers,
                         parse_results.consumed,
                     ))
                 } else if self
                     .headers
                     .has_header_token("Transfer-Encoding", "chunked")
                 {
+                    if self.reject_chunked_with_content_length
+                        && self.headers.has_header("Content-Length")
+                    {
+                        return Err(Error::ChunkedWithContentLength);
+                    }
+                    self.framing = Framing::Chunked;
+                    let mut chunked_body = ChunkedBody::new();
+                    // Decode into the body's own allocation, so that any
+                    // capacity reserved for it ahead of time is put to use.
+                    if self.body.is_empty() {
+                        chunked_body.buffer = std::mem::take(&mut self.body);
+                    }
+                    chunked_body.line_terminator = self.line_terminator;
+                    chunked_body.max_chunk_count = self.max_chunk_count;
+                    chunked_body.max_chunk_ext_bytes = self.max_chunk_ext_bytes;
                     Ok((
                         ParseStatusInternal::CompletePart,
-                        ResponseState::ChunkedBody(ChunkedBody::new()),
+                        ResponseState::ChunkedBody(chunked_body),
                         parse_results.consumed,
                     ))
-                } else {
+                } else if let Some(content_length) =
+                    content_length(&self.headers)?
+                {
+                    if self.on_oversize_body == OnOversizeBody::Error {
+                        self.check_message_size(
+                            self.stats
+                                .total_bytes()
+                                .saturating_add(content_length),
+                        )?;
+                    }
+                    self.framing = Framing::FixedLength(content_length);
+                    self.body
+                        .reserve(content_length.min(MAX_BODY_PREALLOCATION));
                     Ok((
-                        ParseStatusInternal::CompleteWhole,
-                        ResponseState::Headers,
+                        ParseStatusInternal::CompletePart,
+                        ResponseState::FixedBody(content_length),
                         parse_results.consumed,
                     ))
+                } else {
+                    self.framing = Framing::UntilClose;
+                    if self.close_delimited
+                        || self.request_protocol.as_deref() == Some("HTTP/1.0")
+                    {
+                        Ok((
+                            ParseStatusInternal::CompletePart,
+                            ResponseState::UntilCloseBody,
+                            parse_results.consumed,
+                        ))
+                    } else {
+                        Ok((
+                            ParseStatusInternal::CompleteWhole,
+                            ResponseState::Headers,
+                            parse_results.consumed,
+                        ))
+                    }
                 }
             },
             rhymessage::ParseStatus::Incomplete => Ok((
@@ -522,8 +2325,36 @@ impl Response {
         &mut self,
         raw_message: &[u8],
     ) -> Result<(ParseStatusInternal, ResponseState, usize), Error> {
-        match find_crlf(raw_message) {
-            Some(status_line_end) => {
+        // Skip the part of the input already scanned by previous calls, but
+        // back up in case it ended with the carriage return of a CRLF split
+        // across calls.
+        let scan_start = self
+            .status_line_scanned
+            .saturating_sub(self.line_terminator.len() - 1)
+            .min(raw_message.len());
+        let status_line_end = self
+            .line_terminator
+            .find(&raw_message[scan_start..])
+            .map(|status_line_end| scan_start + status_line_end);
+        match (status_line_end, self.status_line_limit) {
+            (Some(0), _)
+                if self.skipped_empty_lines < self.max_leading_empty_lines =>
+            {
+                self.skipped_empty_lines += 1;
+                self.status_line_scanned = 0;
+                Ok((
+                    ParseStatusInternal::CompletePart,
+                    ResponseState::StatusLine,
+                    self.line_terminator.len(),
+                ))
+            },
+            (Some(status_line_end), Some(limit)) if status_line_end > limit => {
+                Err(Error::StatusLineTooLong(raw_message[..limit].to_vec()))
+            },
+            (Some(status_line_end), _) => {
+                self.status_line_scanned = 0;
+                self.skipped_empty_lines = 0;
+                self.begin_message();
                 let status_line = &raw_message[0..status_line_end];
                 let status_line =
                     std::str::from_utf8(status_line).map_err(|source| {
@@ -532,9 +2363,24 @@ impl Response {
                             source,
                         }
                     })?;
-                let consumed = status_line_end + CRLF.len();
-                let (status_code, reason_phrase) =
-                    parse_status_line(status_line)?;
+                let consumed = status_line_end + self.line_terminator.len();
+                self.stats = MessageStats {
+                    head_bytes: consumed,
+                    ..MessageStats::default()
+                };
+                self.check_message_size(consumed)?;
+                self.status_line_range = Some(0..status_line_end);
+                self.headers_range = None;
+                self.framing = Framing::None;
+                self.head_length = consumed;
+                let (protocol, status_code, reason_phrase) =
+                    parse_status_line(
+                        status_line,
+                        self.strict_status_codes,
+                        &self.accepted_protocols,
+                        self.allow_missing_reason_phrase,
+                    )?;
+                self.protocol = protocol.to_string().into();
                 self.status_code = status_code;
                 self.reason_phrase = reason_phrase.to_string().into();
                 Ok((
@@ -543,409 +2389,2947 @@ impl Response {
                     consumed,
                 ))
             },
-            None => Ok((
-                ParseStatusInternal::Incomplete,
-                ResponseState::StatusLine,
-                0,
-            )),
+            (None, Some(limit)) if raw_message.len() > limit => {
+                Err(Error::StatusLineTooLong(raw_message[..limit].to_vec()))
+            },
+            (None, _) => {
+                self.status_line_scanned = raw_message.len();
+                Ok((
+                    ParseStatusInternal::Incomplete,
+                    ResponseState::StatusLine,
+                    0,
+                ))
+            },
         }
     }
-}
 
-impl Default for Response {
-    fn default() -> Self {
-        Self::new()
+    // Take all the given input as more of a body which extends until the
+    // connection is closed, as much as the body read budget allows.
+    fn parse_message_for_until_close_body(
+        &mut self,
+        raw_message: &[u8],
+    ) -> Result<usize, Error> {
+        let raw_message = take_body_budget(
+            &mut self.body_budget_remaining,
+            raw_message,
+            usize::MAX,
+        );
+        self.stats.body_bytes += raw_message.len();
+        if self.body_limit().is_none() {
+            self.check_message_size(self.stats.total_bytes())?;
+        }
+        self.observe_body(raw_message);
+        self.store_body(raw_message);
+        Ok(raw_message.len())
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Report which part of the response the parser will look for next when
+    /// [`parse`](#method.parse) is called with more input.  Once a response is
+    /// completely parsed, the parser is ready for the next one, so this
+    /// returns `ParsePhase::StatusLine`.  This is useful for finding out
+    /// where a slow peer has stalled.
+    #[must_use]
+    pub fn parse_phase(&self) -> ParsePhase {
+        match &self.state {
+            ResponseState::ChunkedBody(chunked_body) => {
+                if chunked_body.is_decoding_trailer() {
+                    ParsePhase::Trailer
+                } else {
+                    ParsePhase::ChunkedBody
+                }
+            },
+            ResponseState::FixedBody(_) | ResponseState::UntilCloseBody => {
+                ParsePhase::Body
+            },
+            ResponseState::Headers => ParsePhase::Headers,
+            ResponseState::StatusLine => ParsePhase::StatusLine,
+        }
+    }
 
-    #![allow(clippy::string_lit_as_bytes)]
+    /// Create a new `416 Range Not Satisfiable` response with no body, for a
+    /// range request none of whose ranges overlap the selected
+    /// representation, which has the given length in bytes.  As described in
+    /// [IETF RFC 7233 section
+    /// 4.4](https://tools.ietf.org/html/rfc7233#section-4.4), the
+    /// `Content-Range` header is set to give that length.  As with
+    /// [`from_status`](#method.from_status), no `Content-Length` header is
+    /// set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let response = Response::range_not_satisfiable(1234);
+    /// assert_eq!(416, response.status_code);
+    /// assert_eq!(
+    ///     Some("bytes */1234"),
+    ///     response.headers.header_value("Content-Range").as_deref()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn range_not_satisfiable(total: u64) -> Self {
+        let mut response = Self::from_status(416);
+        response
+            .headers
+            .set_header("Content-Range", format!("bytes */{total}"));
+        response
+    }
 
-    use super::*;
+    /// Reserve capacity for at least the given number of additional bytes of
+    /// body, to avoid repeatedly reallocating the body as it grows while a
+    /// large response is parsed.  This is most useful for a body which uses
+    /// the "chunked" transfer coding, since its size isn't known ahead of
+    /// time, and may be called either before parsing begins or while the
+    /// body is being decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.reserve_body(65536);
+    /// response
+    ///     .parse(concat!(
+    ///         "HTTP/1.1 200 OK\r\n",
+    ///         "Transfer-Encoding: chunked\r\n",
+    ///         "\r\n",
+    ///         "5\r\nHello\r\n",
+    ///         "0\r\n\r\n",
+    ///     ))
+    ///     .unwrap();
+    /// assert_eq!(b"Hello", &response.body[..]);
+    /// assert!(response.body.capacity() >= 65536);
+    /// ```
+    pub fn reserve_body(
+        &mut self,
+        additional: usize,
+    ) {
+        match &mut self.state {
+            ResponseState::ChunkedBody(chunked_body) => {
+                chunked_body.buffer.reserve(additional);
+            },
+            _ => self.body.reserve(additional),
+        }
+    }
 
-    #[test]
-    fn generate_get_response() {
-        let mut response = Response::new();
-        response.status_code = 200;
-        response.reason_phrase = "OK".into();
-        response.headers.add_header(Header {
-            name: "Date".into(),
-            value: "Mon, 27 Jul 2009 12:28:53 GMT".into(),
-        });
-        response.headers.add_header(Header {
-            name: "Accept-Ranges".into(),
-            value: "bytes".into(),
-        });
-        response.headers.add_header(Header {
-            name: "Content-Type".into(),
-            value: "text/plain".into(),
-        });
-        response.body =
-            "Hello World! My payload includes a trailing CRLF.\r\n".into();
-        response.headers.add_header(Header {
-            name: "Content-Length".into(),
-            value: format!("{}", response.body.len()),
-        });
-        assert!(matches!(
-            response.generate(),
-            Ok(raw_response) if raw_response == format!(
-                concat!(
-                    "HTTP/1.1 200 OK\r\n",
-                    "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-                    "Accept-Ranges: bytes\r\n",
-                    "Content-Type: text/plain\r\n",
-                    "Content-Length: {}\r\n",
-                    "\r\n",
-                    "Hello World! My payload includes a trailing CRLF.\r\n",
-                ),
-                response.body.len()
-            ).as_bytes()
-        ));
+    /// Parse the `Retry-After` header of the response, which a server may
+    /// send with a `503 Service Unavailable` or `429 Too Many Requests`
+    /// response to say when to try again, or return `None` if the response
+    /// has no such header or its value is neither a number of seconds nor an
+    /// HTTP-date.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::{Response, RetryAfter};
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Retry-After", "120");
+    /// assert_eq!(Some(RetryAfter::Seconds(120)), response.retry_after());
+    /// ```
+    #[must_use]
+    pub fn retry_after(&self) -> Option<RetryAfter> {
+        self.headers
+            .header_value("Retry-After")
+            .and_then(RetryAfter::parse)
     }
 
-    #[test]
-    fn parse_get_response_with_body_and_content_length() {
-        let raw_response = concat!(
-            "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-            "Server: Apache\r\n",
-            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
-            "ETag: \"34aa387-d-1568eb00\"\r\n",
-            "Accept-Ranges: bytes\r\n",
-            "Content-Length: 51\r\n",
-            "Vary: Accept-Encoding\r\n",
-            "Content-Type: text/plain\r\n",
-            "\r\n",
-            "Hello World! My payload includes a trailing CRLF.\r\n",
-        );
-        let mut response = Response::new();
-        assert!(matches!(
-            response.parse(raw_response),
-            Ok(ParseResults{
-                status: ParseStatus::Complete,
-                consumed
-            }) if consumed == raw_response.len()
-        ));
-        assert_eq!(200, response.status_code);
-        assert_eq!("OK", response.reason_phrase);
-        assert!(response.headers.has_header("Date"));
-        assert_eq!(
-            Some("Mon, 27 Jul 2009 12:28:53 GMT"),
-            response.headers.header_value("Date").as_deref()
-        );
-        assert!(response.headers.has_header("Accept-Ranges"));
-        assert_eq!(
-            Some("bytes"),
-            response.headers.header_value("Accept-Ranges").as_deref()
-        );
-        assert!(response.headers.has_header("Content-Type"));
-        assert_eq!(
+    /// Replace the body of the response with the given bytes, and set the
+    /// `Content-Length` header to match.  If the new body is empty, the
+    /// `Content-Length` header is removed instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.set_body("Hello, World!");
+    /// assert_eq!(
+    ///     Some("13"),
+    ///     response.headers.header_value("Content-Length").as_deref()
+    /// );
+    /// ```
+    pub fn set_body<T>(
+        &mut self,
+        body: T,
+    ) where
+        T: Into<Vec<u8>>,
+    {
+        self.body = body.into();
+        if self.body.is_empty() {
+            self.headers.remove_header("Content-Length");
+        } else {
+            self.headers
+                .set_header("Content-Length", self.body.len().to_string());
+        }
+    }
+
+    /// Register a function to be called with each segment of the body of
+    /// the response as it is parsed, in order.  The body is still collected in
+    /// the [`body`](#structfield.body) field as usual; this just lets the
+    /// body be observed as it streams in, for example to compute a digest of
+    /// it without reading it again afterwards.  For a body which uses the
+    /// "chunked" transfer coding, the observer sees the decoded data, not the
+    /// chunk framing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    /// use std::sync::{
+    ///     Arc,
+    ///     Mutex,
+    /// };
+    ///
+    /// let seen = Arc::new(Mutex::new(0));
+    /// let mut response = Response::new();
+    /// let seen_by_observer = seen.clone();
+    /// response.set_body_observer(move |segment| {
+    ///     *seen_by_observer.lock().unwrap() += segment.len();
+    /// });
+    /// ```
+    pub fn set_body_observer<F>(
+        &mut self,
+        observer: F,
+    ) where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        self.body_observer = Some(Box::new(observer));
+    }
+
+    /// Choose whether or not the body of the response is delimited by the
+    /// closing of the connection, as described in [IETF RFC 7230 section
+    /// 3.3.3](https://tools.ietf.org/html/rfc7230#section-3.3.3), rather
+    /// than by a `Content-Length` header or the "chunked" transfer coding.
+    /// This is intended for an HTTP/1.0 response, or one which closes the
+    /// connection after it is sent, and is not set by default.
+    ///
+    /// When set, [`generate`](#method.generate) leaves out the
+    /// `Content-Length` and `Transfer-Encoding` headers, emits the body as
+    /// is, and, unless the protocol is `HTTP/1.0`, adds the `close` option to
+    /// the `Connection` header.  The sender must then close the connection
+    /// after sending the response.
+    ///
+    /// Also when set, [`parse`](#method.parse) collects the body of a
+    /// response which has neither a `Content-Length` header nor the
+    /// "chunked" transfer coding, rather than completing the response without
+    /// a body.  The response is then completed by
+    /// [`finalize`](#method.finalize) or
+    /// [`parse_at_eof`](#method.parse_at_eof) once the connection is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.set_body("Hello");
+    /// response.set_close_delimited(true);
+    /// assert_eq!(
+    ///     b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\nHello",
+    ///     &response.generate().unwrap()[..]
+    /// );
+    /// ```
+    pub fn set_close_delimited(
+        &mut self,
+        close_delimited: bool,
+    ) {
+        self.close_delimited = close_delimited;
+    }
+
+    /// Set the maximum size, in bytes, of each header line of the response,
+    /// or remove the limit if `None` is given.  The [`parse`](#method.parse)
+    /// function will return an [`Error::Headers`][Headers] error if a header
+    /// line exceeds this size, and [`generate`](#method.generate) will fold
+    /// longer header lines to fit, if possible.  This is 1000 by default.
+    ///
+    /// [Headers]: enum.Error.html#variant.Headers
+    pub fn set_header_line_limit(
+        &mut self,
+        limit: Option<usize>,
+    ) {
+        self.headers.set_line_limit(limit);
+    }
+
+    /// Replace any `Vary` headers of the response with a single one listing
+    /// the given request header names, or remove them if no names are given.
+    /// Names repeated without regard to case are only listed once.  If any
+    /// of the names is `*`, meaning that the response varies on more than
+    /// request headers, the header lists only `*`, as described in [IETF RFC
+    /// 7231 section 7.1.4](https://tools.ietf.org/html/rfc7231#section-7.1.4).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.set_vary(&["Accept-Encoding", "Accept-Language"]);
+    /// assert_eq!(
+    ///     Some("Accept-Encoding, Accept-Language"),
+    ///     response.headers.header_value("Vary").as_deref()
+    /// );
+    /// ```
+    pub fn set_vary(
+        &mut self,
+        names: &[&str],
+    ) {
+        let names = vary_names(names.iter().copied());
+        if names.is_empty() {
+            self.headers.remove_header("Vary");
+        } else {
+            self.headers.set_header("Vary", names.join(", "));
+        }
+    }
+
+    /// Return the range of byte offsets, relative to the start of the
+    /// response, of the status line, once it has been completely parsed.  The
+    /// range does not include the carriage return and line feed which end
+    /// the line.
+    #[must_use]
+    pub fn status_line_range(&self) -> Option<std::ops::Range<usize>> {
+        self.status_line_range.clone()
+    }
+
+    /// Return the number of bytes consumed by the parser for the head and
+    /// body of the response.  While a response is being parsed, this covers the
+    /// parts parsed so far.
+    #[must_use]
+    pub fn stats(&self) -> MessageStats {
+        self.stats
+    }
+
+    // Add the given segment to the body, keeping only as much of it as fits
+    // within the limit, if the body is to be truncated rather than rejected
+    // when it would make the response exceed its maximum size.
+    fn store_body(
+        &mut self,
+        segment: &[u8],
+    ) {
+        let room = self.body_limit().map_or(segment.len(), |body_limit| {
+            body_limit.saturating_sub(self.body.len())
+        });
+        if segment.len() > room {
+            self.body_truncated = true;
+        }
+        self.body.extend(&segment[..segment.len().min(room)]);
+    }
+
+    /// Remove all hop-by-hop headers from the response, as a proxy must do
+    /// before forwarding it, according to [IETF RFC 7230 section
+    /// 6.1](https://tools.ietf.org/html/rfc7230#section-6.1).  This removes
+    /// any header named as an option in the `Connection` header, along with
+    /// `Connection` itself and the other headers which are always
+    /// hop-by-hop: `Keep-Alive`, `Proxy-Authenticate`, `Proxy-Authorization`,
+    /// `TE`, `Trailer`, `Transfer-Encoding`, and `Upgrade`.
+    ///
+    /// Since `Transfer-Encoding` is removed, this should only be done to a
+    /// response whose body is not chunked, such as one which was parsed (the
+    /// parser removes the chunked coding from the body).
+    pub fn strip_hop_by_hop_headers(&mut self) {
+        strip_hop_by_hop_headers(&mut self.headers);
+    }
+
+    /// Create a new `101 Switching Protocols` response which completes the
+    /// opening handshake of the WebSocket protocol, as specified in [IETF RFC
+    /// 6455 section 4.2.2](https://tools.ietf.org/html/rfc6455#section-4.2.2).
+    /// The `Upgrade`, `Connection`, and `Sec-WebSocket-Accept` headers are set.
+    /// The caller is responsible for computing the given accept key from the
+    /// `Sec-WebSocket-Key` header of the client's request.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let response =
+    ///     Response::switching_protocols("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    /// assert!(matches!(
+    ///     response.generate(),
+    ///     Ok(raw_response) if raw_response == concat!(
+    ///         "HTTP/1.1 101 Switching Protocols\r\n",
+    ///         "Upgrade: websocket\r\n",
+    ///         "Connection: Upgrade\r\n",
+    ///         "Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n",
+    ///         "\r\n",
+    ///     ).as_bytes()
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn switching_protocols<T>(accept_key: T) -> Self
+    where
+        T: AsRef<str>,
+    {
+        let mut response = Self::new();
+        response.status_code = 101;
+        response.reason_phrase = "Switching Protocols".into();
+        response.headers.set_header("Upgrade", "websocket");
+        response.headers.set_header("Connection", "Upgrade");
+        response
+            .headers
+            .set_header("Sec-WebSocket-Accept", accept_key.as_ref());
+        response
+    }
+
+    /// Take any bytes which were fed to [`parse_chunks`](#method.parse_chunks)
+    /// but not consumed by the parser, such as the start of the next message,
+    /// leaving the internal buffer empty.
+    #[must_use]
+    pub fn take_leftover(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.leftover)
+    }
+
+    /// Create a new response with the given status code, along with its
+    /// canonical reason phrase, and the given plain text as the body.  The
+    /// `Content-Type` header is set to `text/plain; charset=utf-8`, and the
+    /// `Content-Length` header is set to match the body.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let response = Response::text(404, "Nothing to see here");
+    /// assert_eq!("Not Found", response.reason_phrase);
+    /// assert!(matches!(
+    ///     response.generate(),
+    ///     Ok(raw_response) if raw_response == concat!(
+    ///         "HTTP/1.1 404 Not Found\r\n",
+    ///         "Content-Type: text/plain; charset=utf-8\r\n",
+    ///         "Content-Length: 19\r\n",
+    ///         "\r\n",
+    ///         "Nothing to see here",
+    ///     ).as_bytes()
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn text(
+        status_code: usize,
+        body: &str,
+    ) -> Self {
+        Self::with_content(status_code, "text/plain; charset=utf-8", body)
+    }
+
+    /// Return the request header names listed in the `Vary` header(s) of the
+    /// response, in the order in which they appear, as described in [IETF
+    /// RFC 7231 section
+    /// 7.1.4](https://tools.ietf.org/html/rfc7231#section-7.1.4).  The lists
+    /// of all the `Vary` headers are combined, and names repeated without
+    /// regard to case are only returned once.  If any of the names is `*`,
+    /// meaning that the response varies on more than request headers, only
+    /// `*` is returned.  The names, except for `*`, may be given to
+    /// [`Request::cache_key`](struct.Request.html#method.cache_key).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymessage::Header;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header("Vary", "Accept-Encoding, Accept");
+    /// response.headers.add_header(Header {
+    ///     name: "Vary".into(),
+    ///     value: "accept-encoding,,Cookie".into(),
+    /// });
+    /// assert_eq!(
+    ///     vec!["Accept-Encoding", "Accept", "Cookie"],
+    ///     response.vary()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn vary(&self) -> Vec<String> {
+        let values = header_values(&self.headers, "Vary");
+        vary_names(values.iter().flat_map(|value| value.split(',')))
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Create a new response value in the same way as [`new`](#method.new),
+    /// but with the parsing limits taken from the given configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::{ParserConfig, Response};
+    ///
+    /// let config = ParserConfig {
+    ///     max_chunk_count: Some(100),
+    ///     ..ParserConfig::default()
+    /// };
+    /// let response = Response::with_config(config);
+    /// assert_eq!(Some(100), response.max_chunk_count);
+    /// ```
+    #[must_use]
+    pub fn with_config(config: ParserConfig) -> Self {
+        let mut response = Self::new();
+        response.headers.set_line_limit(config.header_line_limit);
+        response.max_chunk_count = config.max_chunk_count;
+        response.max_chunk_ext_bytes = config.max_chunk_ext_bytes;
+        response.max_decoded_body_size = config.max_decoded_body_size;
+        response.max_leading_empty_lines = config.max_leading_empty_lines;
+        response.max_message_size = config.max_message_size;
+        response.on_oversize_body = config.on_oversize_body;
+        response.status_line_limit = config.start_line_limit;
+        response
+    }
+
+    /// Return the authentication challenges listed in the
+    /// `WWW-Authenticate` header(s) of the response, in the order in which
+    /// they appear.  Malformed challenges are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::Response;
+    ///
+    /// let mut response = Response::new();
+    /// response.headers.set_header(
+    ///     "WWW-Authenticate",
+    ///     "Newauth realm=\"apps\", type=1, Basic realm=\"simple\"",
+    /// );
+    /// let challenges = response.www_authenticate();
+    /// assert_eq!(2, challenges.len());
+    /// assert_eq!("Newauth", challenges[0].scheme);
+    /// assert_eq!(Some("1"), challenges[0].parameter("type"));
+    /// assert_eq!("Basic", challenges[1].scheme);
+    /// assert_eq!(Some("simple"), challenges[1].parameter("realm"));
+    /// ```
+    #[must_use]
+    pub fn www_authenticate(&self) -> Vec<Challenge> {
+        header_values(&self.headers, "WWW-Authenticate")
+            .iter()
+            .flat_map(|value| parse_challenges(value))
+            .collect()
+    }
+
+    fn with_content(
+        status_code: usize,
+        content_type: &'static str,
+        body: &str,
+    ) -> Self {
+        let mut response = Self::new();
+        response.status_code = status_code;
+        response.reason_phrase = canonical_reason_phrase(status_code).into();
+        response.headers.set_header("Content-Type", content_type);
+        response.set_body(body);
+        response
+    }
+}
+
+impl std::fmt::Debug for Response {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if f.alternate() {
+            f.debug_struct("Response")
+                .field("accepted_protocols", &self.accepted_protocols)
+                .field(
+                    "allow_missing_reason_phrase",
+                    &self.allow_missing_reason_phrase,
+                )
+                .field("body", &self.body)
+                .field("body_budget_remaining", &self.body_budget_remaining)
+                .field(
+                    "body_observer",
+                    &self.body_observer.as_ref().map(|_| "FnMut(&[u8])"),
+                )
+                .field("body_read_budget", &self.body_read_budget)
+                .field("body_truncated", &self.body_truncated)
+                .field("close_delimited", &self.close_delimited)
+                .field(
+                    "combine_repeated_headers",
+                    &self.combine_repeated_headers,
+                )
+                .field("complete", &self.complete)
+                .field(
+                    "decode_content_encoding",
+                    &self.decode_content_encoding,
+                )
+                .field("framing", &self.framing)
+                .field("head_length", &self.head_length)
+                .field("headers", &self.headers)
+                .field("headers_range", &self.headers_range)
+                .field("leftover", &self.leftover)
+                .field("line_terminator", &self.line_terminator)
+                .field("max_chunk_count", &self.max_chunk_count)
+                .field("max_chunk_ext_bytes", &self.max_chunk_ext_bytes)
+                .field("max_decoded_body_size", &self.max_decoded_body_size)
+                .field("max_leading_empty_lines", &self.max_leading_empty_lines)
+                .field("max_message_size", &self.max_message_size)
+                .field("never_combined_headers", &self.never_combined_headers)
+                .field("on_oversize_body", &self.on_oversize_body)
+                .field("protocol", &self.protocol)
+                .field("reason_phrase", &self.reason_phrase)
+                .field(
+                    "reject_chunked_with_content_length",
+                    &self.reject_chunked_with_content_length,
+                )
+                .field("reject_trailing_data", &self.reject_trailing_data)
+                .field("request_method", &self.request_method)
+                .field("request_protocol", &self.request_protocol)
+                .field("skipped_empty_lines", &self.skipped_empty_lines)
+                .field("state", &self.state)
+                .field("stats", &self.stats)
+                .field("status_code", &self.status_code)
+                .field("status_line_limit", &self.status_line_limit)
+                .field("status_line_range", &self.status_line_range)
+                .field("status_line_scanned", &self.status_line_scanned)
+                .field("strict_status_codes", &self.strict_status_codes)
+                .field("trailer", &self.trailer)
+                .field("trailer_headers", &self.trailer_headers)
+                .field("trailer_merge_strategy", &self.trailer_merge_strategy)
+                .finish()
+        } else {
+            f.debug_struct("Response")
+                .field("status_code", &self.status_code)
+                .field("reason_phrase", &self.reason_phrase)
+                .field("headers", &self.headers.headers().len())
+                .field("body", &BodyPreview(&self.body))
+                .finish()
+        }
+    }
+}
+
+impl Default for Response {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #![allow(clippy::string_lit_as_bytes)]
+
+    use super::*;
+    use rhymessage::Header;
+    use std::io::Read;
+
+    #[test]
+    fn set_body_sets_content_length() {
+        let mut response = Response::new();
+        response.set_body("FeelsGoodMan");
+        assert_eq!(b"FeelsGoodMan", &response.body[..]);
+        assert_eq!(
+            Some("12"),
+            response.headers.header_value("Content-Length").as_deref()
+        );
+        response.set_body(vec![0; 100]);
+        assert_eq!(100, response.body.len());
+        assert_eq!(vec!["100"], response.header_values("Content-Length"));
+    }
+
+    #[test]
+    fn body_reader_reads_body() {
+        let mut response = Response::new();
+        let mut body = Vec::new();
+        response.body_reader().read_to_end(&mut body).unwrap();
+        assert!(body.is_empty());
+        response.set_body("FeelsGoodMan");
+        response.body_reader().read_to_end(&mut body).unwrap();
+        assert_eq!(b"FeelsGoodMan", &body[..]);
+    }
+
+    #[test]
+    fn clear_body_removes_body_and_framing_headers() {
+        let mut response = Response::new();
+        response.headers.set_header("Content-Encoding", "gzip");
+        response.headers.set_header("Transfer-Encoding", "chunked");
+        response.headers.set_header("X-Foo", "Bar");
+        response.set_body("FeelsGoodMan");
+        response.clear_body();
+        assert!(response.body.is_empty());
+        assert!(!response.headers.has_header("Content-Encoding"));
+        assert!(!response.headers.has_header("Content-Length"));
+        assert!(!response.headers.has_header("Transfer-Encoding"));
+        assert_eq!(
+            Some("Bar"),
+            response.headers.header_value("X-Foo").as_deref()
+        );
+    }
+
+    #[test]
+    fn set_body_empty_removes_content_length() {
+        let mut response = Response::new();
+        response.set_body("FeelsGoodMan");
+        response.set_body(Vec::new());
+        assert!(response.body.is_empty());
+        assert!(!response.headers.has_header("Content-Length"));
+    }
+
+    #[test]
+    fn parse_phase_follows_parser() {
+        let mut response = Response::new();
+        assert_eq!(ParsePhase::StatusLine, response.parse_phase());
+        let pieces: &[(&str, ParsePhase)] = &[
+            ("HTTP/1.1 200 OK\r\n", ParsePhase::Headers),
+            ("Transfer-Encoding: chunked\r\n\r\n", ParsePhase::ChunkedBody),
+            ("5\r\nHello\r\n", ParsePhase::ChunkedBody),
+            ("0\r\n", ParsePhase::Trailer),
+            ("\r\n", ParsePhase::StatusLine),
+        ];
+        let mut input = String::new();
+        for (piece, phase) in pieces {
+            input.push_str(piece);
+            let consumed = response.parse(&input).unwrap().consumed;
+            input.drain(..consumed);
+            assert_eq!(*phase, response.parse_phase(), "{piece}");
+        }
+        let mut response = Response::new();
+        assert!(response
+            .parse("HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHe")
+            .is_ok());
+        assert_eq!(ParsePhase::Body, response.parse_phase());
+        assert!(response.parse("llo").is_ok());
+        assert_eq!(ParsePhase::StatusLine, response.parse_phase());
+    }
+
+    #[test]
+    fn generate_get_response() {
+        let mut response = Response::new();
+        response.status_code = 200;
+        response.reason_phrase = "OK".into();
+        response.headers.add_header(Header {
+            name: "Date".into(),
+            value: "Mon, 27 Jul 2009 12:28:53 GMT".into(),
+        });
+        response.headers.add_header(Header {
+            name: "Accept-Ranges".into(),
+            value: "bytes".into(),
+        });
+        response.headers.add_header(Header {
+            name: "Content-Type".into(),
+            value: "text/plain".into(),
+        });
+        response.body =
+            "Hello World! My payload includes a trailing CRLF.\r\n".into();
+        response.headers.add_header(Header {
+            name: "Content-Length".into(),
+            value: format!("{}", response.body.len()),
+        });
+        assert!(matches!(
+            response.generate(),
+            Ok(raw_response) if raw_response == format!(
+                concat!(
+                    "HTTP/1.1 200 OK\r\n",
+                    "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+                    "Accept-Ranges: bytes\r\n",
+                    "Content-Type: text/plain\r\n",
+                    "Content-Length: {}\r\n",
+                    "\r\n",
+                    "Hello World! My payload includes a trailing CRLF.\r\n",
+                ),
+                response.body.len()
+            ).as_bytes()
+        ));
+    }
+
+    #[test]
+    fn debug_format_is_compact() {
+        let mut response = Response::new();
+        response.status_code = 404;
+        response.reason_phrase = "Not Found".into();
+        response.headers.set_header("Content-Type", "text/plain");
+        response.body = vec![b'X'; 1000];
+        assert_eq!(
+            format!(
+                concat!(
+                    "Response {{ status_code: 404, reason_phrase: ",
+                    "\"Not Found\", headers: 1, body: 1000 bytes \"{}\"... }}",
+                ),
+                "X".repeat(32)
+            ),
+            format!("{response:?}")
+        );
+    }
+
+    #[test]
+    fn debug_format_alternate_is_complete() {
+        let mut response = Response::new();
+        response.body = vec![b'X'; 1000];
+        let debug = format!("{response:#?}");
+        assert!(debug.contains("max_chunk_count"));
+        assert_eq!(1000, debug.matches("88,").count());
+    }
+
+    #[test]
+    fn generate_switching_protocols_response() {
+        let response =
+            Response::switching_protocols("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        assert_eq!(101, response.status_code);
+        assert_eq!("Switching Protocols", response.reason_phrase);
+        assert!(response.headers.has_header_token("Upgrade", "websocket"));
+        assert!(response.headers.has_header_token("Connection", "upgrade"));
+        assert_eq!(
+            Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="),
+            response.headers.header_value("Sec-WebSocket-Accept").as_deref()
+        );
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn generate_interim_responses_without_body() {
+        let mut response = Response::continue_interim();
+        response.set_body("Hello");
+        response.headers.set_header("Transfer-Encoding", "chunked");
+        response.trailer_headers.set_header("X-Foo", "Bar");
+        assert!(matches!(
+            response.generate(),
+            Ok(raw_response) if raw_response == b"HTTP/1.1 100 Continue\r\n\r\n"
+        ));
+        let mut response = Response::interim(102, "Processing").unwrap();
+        response.set_body("Hello");
+        assert!(response.headers.has_header("Content-Length"));
+        assert!(matches!(
+            response.generate(),
+            Ok(raw_response) if raw_response == b"HTTP/1.1 102 Processing\r\n\r\n"
+        ));
+        assert!(matches!(
+            Response::interim(200, "OK"),
+            Err(Error::StatusCodeOutOfRange(200))
+        ));
+    }
+
+    #[test]
+    fn with_config_applies_limits() {
+        let config = ParserConfig {
+            header_line_limit: Some(20),
+            max_chunk_count: Some(2),
+            max_chunk_ext_bytes: Some(8),
+            max_decoded_body_size: Some(50),
+            max_leading_empty_lines: 1,
+            max_message_size: Some(100),
+            on_oversize_body: OnOversizeBody::Truncate,
+            start_line_limit: Some(10),
+        };
+        let response = Response::with_config(config);
+        assert_eq!(Some(2), response.max_chunk_count);
+        assert_eq!(Some(8), response.max_chunk_ext_bytes);
+        assert_eq!(Some(50), response.max_decoded_body_size);
+        assert_eq!(1, response.max_leading_empty_lines);
+        assert_eq!(Some(100), response.max_message_size);
+        assert_eq!(OnOversizeBody::Truncate, response.on_oversize_body);
+        assert_eq!(Some(10), response.status_line_limit);
+        let mut response = Response::with_config(config);
+        assert!(matches!(
+            response.parse("HTTP/1.1 200 OK\r\n"),
+            Err(Error::StatusLineTooLong(_))
+        ));
+        let mut response = Response::with_config(ParserConfig {
+            start_line_limit: None,
+            ..config
+        });
+        assert!(matches!(
+            response.parse(concat!(
+                "\r\n",
+                "HTTP/1.1 200 OK\r\n",
+                "X-Long: This header line is too long\r\n",
+                "\r\n",
+            )),
+            Err(Error::Headers(rhymessage::Error::HeaderLineTooLong(_)))
+        ));
+    }
+
+    #[test]
+    fn content_type() {
+        let response = Response::json(200, "{}");
+        assert!(matches!(
+            response.content_type(),
+            Some(MediaType {
+                r#type,
+                subtype,
+                ..
+            }) if r#type == "application" && subtype == "json"
+        ));
+        assert_eq!(None, Response::new().content_type());
+    }
+
+    #[test]
+    fn text_response() {
+        let response = Response::text(503, "Try again later");
+        assert_eq!(503, response.status_code);
+        assert_eq!("Service Unavailable", response.reason_phrase);
+        assert_eq!(
+            Some("text/plain; charset=utf-8"),
+            response.headers.header_value("Content-Type").as_deref()
+        );
+        assert_eq!(
+            Some("15"),
+            response.headers.header_value("Content-Length").as_deref()
+        );
+        assert_eq!(b"Try again later", &response.body[..]);
+    }
+
+    #[test]
+    fn json_response() {
+        let response = Response::json(200, "[]");
+        assert_eq!(200, response.status_code);
+        assert_eq!("OK", response.reason_phrase);
+        assert_eq!(
+            Some("application/json"),
+            response.headers.header_value("Content-Type").as_deref()
+        );
+        assert_eq!(
+            Some("2"),
+            response.headers.header_value("Content-Length").as_deref()
+        );
+        assert_eq!(b"[]", &response.body[..]);
+    }
+
+    #[test]
+    fn text_response_unknown_status_code_has_empty_reason_phrase() {
+        let response = Response::text(299, "");
+        assert_eq!(299, response.status_code);
+        assert_eq!("", response.reason_phrase);
+        assert!(response.body.is_empty());
+        assert!(!response.headers.has_header("Content-Length"));
+    }
+
+    #[test]
+    fn error_responses() {
+        for (response, status_code, reason_phrase) in [
+            (Response::bad_request(), 400, "Bad Request"),
+            (Response::not_found(), 404, "Not Found"),
+            (
+                Response::method_not_allowed(&["GET"]),
+                405,
+                "Method Not Allowed",
+            ),
+            (
+                Response::range_not_satisfiable(0),
+                416,
+                "Range Not Satisfiable",
+            ),
+            (Response::internal_error(), 500, "Internal Server Error"),
+            (Response::internal_server_error(), 500, "Internal Server Error"),
+        ] {
+            assert_eq!(status_code, response.status_code);
+            assert_eq!(reason_phrase, response.reason_phrase);
+            assert!(response.body.is_empty());
+            assert!(!response.headers.has_header("Content-Length"));
+        }
+    }
+
+    #[test]
+    fn from_status_sets_no_content_length() {
+        for (status_code, reason_phrase) in [
+            (101, "Switching Protocols"),
+            (204, "No Content"),
+            (304, "Not Modified"),
+            (429, "Too Many Requests"),
+            (500, "Internal Server Error"),
+        ] {
+            let response = Response::from_status(status_code);
+            assert_eq!(status_code, response.status_code);
+            assert_eq!(reason_phrase, response.reason_phrase);
+            assert!(response.body.is_empty());
+            assert!(response.headers.headers().is_empty());
+        }
+    }
+
+    #[test]
+    fn method_not_allowed_lists_allowed_methods() {
+        let response = Response::method_not_allowed(&["GET", "HEAD", "POST"]);
+        assert_eq!(
+            Some("GET, HEAD, POST"),
+            response.headers.header_value("Allow").as_deref()
+        );
+    }
+
+    #[test]
+    fn range_not_satisfiable_gives_representation_length() {
+        let response = Response::range_not_satisfiable(47022);
+        assert!(matches!(
+            response.generate(),
+            Ok(raw_response) if raw_response == concat!(
+                "HTTP/1.1 416 Range Not Satisfiable\r\n",
+                "Content-Range: bytes */47022\r\n",
+                "\r\n",
+            ).as_bytes()
+        ));
+    }
+
+    #[test]
+    fn reserve_body_while_decoding_chunked_body() {
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Transfer-Encoding: chunked\r\n",
+                "\r\n",
+                "5\r\nHello\r\n",
+            )),
+            Ok(ParseResults {
+                status: ParseStatus::Incomplete,
+                ..
+            })
+        ));
+        response.reserve_body(10000);
+        assert!(matches!(
+            response.parse("0\r\n\r\n"),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+        assert_eq!(b"Hello", &response.body[..]);
+        assert!(response.body.capacity() >= 10005);
+    }
+
+    #[test]
+    fn retry_after() {
+        let mut response = Response::new();
+        response.status_code = 503;
+        assert_eq!(None, response.retry_after());
+        response
+            .headers
+            .set_header("Retry-After", "Fri, 31 Dec 1999 23:59:59 GMT");
+        assert_eq!(
+            Some(RetryAfter::Date(
+                std::time::SystemTime::UNIX_EPOCH
+                    + std::time::Duration::from_secs(946_684_799)
+            )),
+            response.retry_after()
+        );
+        response.headers.set_header("Retry-After", "tomorrow");
+        assert_eq!(None, response.retry_after());
+    }
+
+    #[test]
+    fn generate_head_then_body_matches_generate() {
+        let mut response = Response::text(200, "Hello, World!");
+        response.headers.set_header("Date", "Mon, 27 Jul 2009 12:28:53 GMT");
+        let head = response.generate_head().unwrap();
+        assert_eq!(
+            concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Content-Type: text/plain; charset=utf-8\r\n",
+                "Content-Length: 13\r\n",
+                "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+                "\r\n",
+            )
+            .as_bytes(),
+            &head[..]
+        );
+        let mut raw_response = head;
+        raw_response.extend(&response.body);
+        assert_eq!(response.generate().unwrap(), raw_response);
+    }
+
+    #[test]
+    fn parse_then_generate_preserves_header_name_casing() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "x-CUSTOM-header: 1\r\n",
+            "set-COOKIE: a=1\r\n",
+            "content-length: 5\r\n",
+            "\r\n",
+            "Hello",
+        );
+        let mut response = Response::new();
+        response.parse(raw_response).unwrap();
+        assert_eq!(raw_response.as_bytes(), &response.generate().unwrap()[..]);
+    }
+
+    #[test]
+    fn generate_chunked_round_trips_through_parser() {
+        let mut response = Response::text(200, "ignored");
+        response.headers.set_header("Transfer-Encoding", "gzip");
+        response.trailer_headers.set_header("X-Checksum", "12345");
+        let chunks = vec![
+            b"Hello".to_vec(),
+            Vec::new(),
+            b", ".to_vec(),
+            b"World!".to_vec(),
+        ];
+        let mut raw_response = Vec::new();
+        response.generate_chunked(chunks, &mut raw_response).unwrap();
+        let mut parsed_response = Response::new();
+        assert!(matches!(
+            parsed_response.parse(&raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+        assert_eq!(b"Hello, World!", &parsed_response.body[..]);
+        assert_eq!(
+            Some("gzip"),
+            parsed_response
+                .headers
+                .header_value("Transfer-Encoding")
+                .as_deref()
+        );
+        assert_eq!(
+            Some("12345"),
+            parsed_response.headers.header_value("X-Checksum").as_deref()
+        );
+    }
+
+    #[test]
+    fn generate_chunked_informational_response_has_no_body() {
+        let mut raw_response = Vec::new();
+        Response::continue_interim()
+            .generate_chunked(vec![b"Hello".to_vec()], &mut raw_response)
+            .unwrap();
+        assert_eq!(b"HTTP/1.1 100 Continue\r\n\r\n", &raw_response[..]);
+    }
+
+    #[test]
+    fn generate_head_announces_trailer() {
+        let mut response = Response::new();
+        response.headers.set_header("Transfer-Encoding", "chunked");
+        response.trailer_headers.set_header("X-Checksum", "12345");
+        assert!(matches!(
+            response.generate_head(),
+            Ok(raw_head) if raw_head == concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Transfer-Encoding: chunked\r\n",
+                "Trailer: X-Checksum\r\n",
+                "\r\n",
+            ).as_bytes()
+        ));
+    }
+
+    #[test]
+    fn generate_chunked_response_with_trailer() {
+        let mut response = Response::new();
+        response.headers.set_header("Content-Type", "text/plain");
+        response.headers.set_header("Transfer-Encoding", "chunked");
+        response.body = "Hello, World!".into();
+        response.trailer_headers.set_header("X-Checksum", "12345");
+        response.trailer_headers.set_header("X-Foo", "Bar");
+        assert!(matches!(
+            response.generate(),
+            Ok(raw_response) if raw_response == concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Content-Type: text/plain\r\n",
+                "Transfer-Encoding: chunked\r\n",
+                "Trailer: X-Checksum, X-Foo\r\n",
+                "\r\n",
+                "D\r\n",
+                "Hello, World!\r\n",
+                "0\r\n",
+                "X-Checksum: 12345\r\n",
+                "X-Foo: Bar\r\n",
+                "\r\n",
+            ).as_bytes()
+        ));
+    }
+
+    #[test]
+    fn generate_then_parse_chunked_response_with_trailer() {
+        let mut response = Response::new();
+        response.headers.set_header("Transfer-Encoding", "chunked");
+        response.body = "Hello, World!".into();
+        response.trailer_headers.set_header("X-Foo", "Bar");
+        let raw_response = response.generate().unwrap();
+        let mut parsed_response = Response::new();
+        assert!(matches!(
+            parsed_response.parse(&raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+        assert_eq!(response.body, parsed_response.body);
+        assert_eq!(
+            Some("Bar"),
+            parsed_response.headers.header_value("X-Foo").as_deref()
+        );
+        assert!(!parsed_response.headers.has_header("Trailer"));
+    }
+
+    #[test]
+    fn parse_response_trailer_colliding_with_header() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "X-Foo: Header\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+            "5\r\nHello\r\n",
+            "0\r\n",
+            "X-Foo: Trailer\r\n",
+            "\r\n",
+        );
+        for (trailer_merge_strategy, expected_values) in &[
+            (TrailerMergeStrategy::Append, vec!["Header", "Trailer"]),
+            (TrailerMergeStrategy::Overwrite, vec!["Trailer"]),
+            (TrailerMergeStrategy::Ignore, vec!["Header"]),
+        ] {
+            let mut response = Response::new();
+            response.trailer_merge_strategy = *trailer_merge_strategy;
+            assert!(matches!(
+                response.parse(raw_response),
+                Ok(ParseResults {
+                    status: ParseStatus::Complete,
+                    ..
+                })
+            ));
+            assert_eq!(
+                *expected_values,
+                response
+                    .headers
+                    .headers()
+                    .iter()
+                    .filter(|header| header.name == "X-Foo")
+                    .map(|header| header.value.as_str())
+                    .collect::<Vec<_>>(),
+                "{trailer_merge_strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_get_response_with_body_and_content_length() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Server: Apache\r\n",
+            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
+            "ETag: \"34aa387-d-1568eb00\"\r\n",
+            "Accept-Ranges: bytes\r\n",
+            "Content-Length: 51\r\n",
+            "Vary: Accept-Encoding\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "Hello World! My payload includes a trailing CRLF.\r\n",
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+        assert_eq!(200, response.status_code);
+        assert_eq!("OK", response.reason_phrase);
+        assert!(response.headers.has_header("Date"));
+        assert_eq!(
+            Some("Mon, 27 Jul 2009 12:28:53 GMT"),
+            response.headers.header_value("Date").as_deref()
+        );
+        assert!(response.headers.has_header("Accept-Ranges"));
+        assert_eq!(
+            Some("bytes"),
+            response.headers.header_value("Accept-Ranges").as_deref()
+        );
+        assert!(response.headers.has_header("Content-Type"));
+        assert_eq!(
+            Some("text/plain"),
+            response.headers.header_value("Content-Type").as_deref()
+        );
+        assert!(response.headers.has_header("Content-Length"));
+        assert_eq!(
+            Some("51"),
+            response.headers.header_value("Content-Length").as_deref()
+        );
+        assert_eq!(
+            "Hello World! My payload includes a trailing CRLF.\r\n".as_bytes(),
+            response.body
+        );
+    }
+
+    #[test]
+    fn parse_get_response_with_chunked_body_no_other_transfer_coding() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Server: Apache\r\n",
+            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
+            "ETag: \"34aa387-d-1568eb00\"\r\n",
+            "Accept-Ranges: bytes\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "Vary: Accept-Encoding\r\n",
+            "Content-Type: text/plain\r\n",
+            "Trailer: X-Foo\r\n",
+            "\r\n",
+            "C\r\n",
+            "Hello World!\r\n",
+            "16\r\n",
+            " My payload includes a\r\n",
+            "11\r\n",
+            " trailing CRLF.\r\n\r\n",
+            "0\r\n",
+            "X-Foo: Bar\r\n",
+            "\r\n",
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+        assert_eq!(200, response.status_code);
+        assert_eq!("OK", response.reason_phrase);
+        assert!(response.headers.has_header("Date"));
+        assert_eq!(
+            Some("Mon, 27 Jul 2009 12:28:53 GMT"),
+            response.headers.header_value("Date").as_deref()
+        );
+        assert!(response.headers.has_header("Accept-Ranges"));
+        assert_eq!(
+            Some("bytes"),
+            response.headers.header_value("Accept-Ranges").as_deref()
+        );
+        assert!(response.headers.has_header("Content-Type"));
+        assert_eq!(
             Some("text/plain"),
             response.headers.header_value("Content-Type").as_deref()
         );
-        assert!(response.headers.has_header("Content-Length"));
         assert_eq!(
-            Some("51"),
-            response.headers.header_value("Content-Length").as_deref()
+            Some("Bar"),
+            response.headers.header_value("X-Foo").as_deref()
+        );
+        assert_eq!(
+            Some("51"),
+            response.headers.header_value("Content-Length").as_deref()
+        );
+        assert_eq!(
+            "Hello World! My payload includes a trailing CRLF.\r\n".as_bytes(),
+            response.body
+        );
+        assert!(!response
+            .headers
+            .has_header_token("Transfer-Encoding", "chunked"));
+        assert!(!response.headers.has_header("Trailer"));
+        assert!(!response.headers.has_header("Transfer-Encoding"));
+        assert_eq!(
+            "Hello World! My payload includes a trailing CRLF.\r\n".as_bytes(),
+            response.body
+        );
+    }
+
+    #[test]
+    fn parse_get_response_with_chunked_body_with_other_transfer_coding() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Transfer-Encoding: foobar, chunked\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "0\r\n",
+            "\r\n",
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+        assert_eq!(
+            Some("foobar"),
+            response.headers.header_value("Transfer-Encoding").as_deref()
+        );
+    }
+
+    #[test]
+    fn connection_options() {
+        let mut response = Response::new();
+        assert!(response.connection_options().is_empty());
+        response.headers.set_header("Connection", "Keep-Alive, X-Foo,");
+        response.headers.add_header(Header {
+            name: "Connection".into(),
+            value: "Upgrade".into(),
+        });
+        assert_eq!(
+            vec!["keep-alive", "x-foo", "upgrade"],
+            response.connection_options()
+        );
+    }
+
+    #[test]
+    fn strip_hop_by_hop_headers() {
+        let mut response = Response::new();
+        response.headers.set_header("Content-Type", "text/plain");
+        response.headers.set_header("Connection", "keep-alive, X-Foo");
+        response.headers.set_header("Keep-Alive", "timeout=5");
+        response.headers.set_header("X-Foo", "Bar");
+        response.headers.set_header("X-Bar", "Foo");
+        response.headers.set_header("Upgrade", "websocket");
+        response
+            .headers
+            .set_header("Proxy-Authorization", "Basic Zm9vOmJhcg==");
+        response.headers.set_header("TE", "trailers");
+        response.strip_hop_by_hop_headers();
+        assert_eq!(
+            vec!["Content-Type", "X-Bar"],
+            response
+                .headers
+                .headers()
+                .iter()
+                .map(|header| header.name.as_ref())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn body_observer_sees_chunked_body_as_it_arrives() {
+        let observed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut response = Response::new();
+        let observed_by_observer = observed.clone();
+        response.set_body_observer(move |segment| {
+            observed_by_observer.lock().unwrap().push(segment.to_vec());
+        });
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+            "D\r\nHello, World!\r\n",
+            "0\r\n\r\n",
+        );
+        let split = raw_response.find("World").unwrap();
+        let consumed = response.parse(&raw_response[..split]).unwrap().consumed;
+        assert!(matches!(
+            response.parse(&raw_response[consumed..]),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+        assert_eq!(
+            vec![b"Hello, ".to_vec(), b"World!".to_vec()],
+            *observed.lock().unwrap()
+        );
+        assert_eq!(b"Hello, World!", &response.body[..]);
+    }
+
+    #[test]
+    fn parse_response_with_chunked_and_content_length_prefers_chunked() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 3\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+            "5\r\nHello\r\n",
+            "0\r\n\r\n",
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+        assert_eq!(b"Hello", &response.body[..]);
+        assert!(response.trailer.is_empty());
+    }
+
+    #[test]
+    fn parse_response_with_chunked_and_content_length_rejected_when_strict() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 3\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+            "5\r\nHello\r\n",
+            "0\r\n\r\n",
+        );
+        let mut response = Response::new();
+        response.reject_chunked_with_content_length = true;
+        assert!(matches!(
+            response.parse(raw_response),
+            Err(Error::ChunkedWithContentLength)
+        ));
+    }
+
+    #[test]
+    fn parse_chunks_across_boundaries() {
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse_chunks(vec![
+                b"HTTP/1.1 200 OK\r\nTransfer-Enc".to_vec(),
+                b"oding: chunked\r\n\r\n5\r\nHel".to_vec(),
+            ]),
+            Ok(ParseStatus::Incomplete)
+        ));
+        assert!(matches!(
+            response
+                .parse_chunks(vec![b"lo\r\n0\r".to_vec(), b"\n\r\n".to_vec(),]),
+            Ok(ParseStatus::Complete)
+        ));
+        assert_eq!(b"Hello", &response.body[..]);
+        assert!(response.take_leftover().is_empty());
+        assert!(matches!(
+            response.parse_chunks(vec![concat!(
+                "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nHi",
+                "HTTP/1.1 204 No Content\r\n\r\n",
+            )
+            .into()]),
+            Ok(ParseStatus::Complete)
+        ));
+        assert_eq!(b"Hi", &response.body[..]);
+        assert!(matches!(
+            response.parse_chunks(std::iter::empty()),
+            Ok(ParseStatus::Complete)
+        ));
+        assert_eq!(204, response.status_code);
+        assert!(response.headers.headers().is_empty());
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn head_ranges_refer_to_original_input() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Type: text/plain\r\n",
+            "Content-Length: 5\r\n",
+            "\r\n",
+            "Hello",
+        );
+        let mut response = Response::new();
+        assert!(response.status_line_range().is_none());
+        assert!(response.headers_range().is_none());
+        let split = raw_response.find("Length").unwrap();
+        let consumed = response.parse(&raw_response[..split]).unwrap().consumed;
+        assert_eq!(Some(0..15), response.status_line_range());
+        assert!(response.headers_range().is_none());
+        assert!(response.parse(&raw_response[consumed..]).is_ok());
+        assert_eq!(
+            "HTTP/1.1 200 OK",
+            &raw_response[response.status_line_range().unwrap()]
+        );
+        assert_eq!(
+            "Content-Type: text/plain\r\nContent-Length: 5\r\n\r\n",
+            &raw_response[response.headers_range().unwrap()]
+        );
+    }
+
+    #[test]
+    fn location_absolute() {
+        let mut response = Response::new();
+        response.status_code = 301;
+        response.headers.set_header("Location", "https://example.com/new");
+        let base = Uri::parse("http://www.example.com/old").unwrap();
+        for base in &[None, Some(&base)] {
+            assert!(matches!(
+                response.location(*base),
+                Some(Ok(location))
+                    if location.to_string() == "https://example.com/new"
+            ));
+        }
+    }
+
+    #[test]
+    fn location_relative_without_base() {
+        let mut response = Response::new();
+        response.status_code = 303;
+        response.headers.set_header("Location", "/see/other");
+        assert!(matches!(
+            response.location(None),
+            Some(Ok(location))
+                if location.is_relative_reference()
+                    && location.to_string() == "/see/other"
+        ));
+    }
+
+    #[test]
+    fn location_malformed() {
+        let mut response = Response::new();
+        response.status_code = 302;
+        response.headers.set_header("Location", "http://[::1/");
+        assert!(matches!(
+            response.location(None),
+            Some(Err(Error::LocationUriInvalid(_)))
+        ));
+    }
+
+    #[test]
+    fn location_missing() {
+        let mut response = Response::new();
+        assert!(response.location(None).is_none());
+        response.status_code = 304;
+        assert!(response.location(None).is_none());
+        response.status_code = 307;
+        assert!(matches!(
+            response.location(None),
+            Some(Err(Error::MissingLocationHeader))
+        ));
+    }
+
+    #[test]
+    fn content_length_after_chunked_decoding() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+            "5\r\nHello\r\n",
+            "0\r\n\r\n",
+        );
+        let mut response = Response::new();
+        assert!(matches!(response.content_length(), Ok(None)));
+        assert!(response.parse(raw_response).is_ok());
+        assert!(matches!(response.content_length(), Ok(Some(5))));
+        response.headers.set_header("Content-Length", "five");
+        assert!(matches!(
+            response.content_length(),
+            Err(Error::InvalidContentLength(_))
+        ));
+        response.headers.set_header("Content-Length", "-1");
+        assert!(matches!(
+            response.content_length(),
+            Err(Error::SignedContentLength(value)) if value == "-1"
+        ));
+        response.headers.set_header("Content-Length", "+5");
+        assert!(matches!(
+            response.content_length(),
+            Err(Error::SignedContentLength(value)) if value == "+5"
+        ));
+    }
+
+    #[test]
+    fn header_values_set_cookie() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Set-Cookie: SID=31d4d96e407aad42; Expires=Wed, 09 Jun 2021 10:18:14 GMT\r\n",
+            "Content-Length: 0\r\n",
+            "Set-Cookie: lang=en-US\r\n",
+            "\r\n",
+        );
+        let mut response = Response::new();
+        assert!(response.parse(raw_response).is_ok());
+        assert_eq!(
+            vec![
+                "SID=31d4d96e407aad42; Expires=Wed, 09 Jun 2021 10:18:14 GMT",
+                "lang=en-US",
+            ],
+            response.header_values("Set-Cookie")
+        );
+    }
+
+    #[test]
+    fn parse_incomplete_body_response() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Server: Apache\r\n",
+            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
+            "ETag: \"34aa387-d-1568eb00\"\r\n",
+            "Accept-Ranges: bytes\r\n",
+            "Content-Length: 52\r\n",
+            "Vary: Accept-Encoding\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "Hello World! My payload includes a trailing CRLF.\r\n",
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Incomplete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+    }
+
+    #[test]
+    fn is_not_complete_while_body_incomplete() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 52\r\n",
+            "\r\n",
+            "Hello World! My payload includes a trailing CRLF.\r\n",
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Incomplete,
+                ..
+            })
+        ));
+        assert!(!response.is_complete());
+    }
+
+    #[test]
+    fn is_complete_after_parsing_whole_response() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 5\r\n",
+            "\r\n",
+            "Hello",
+        );
+        let mut response = Response::new();
+        let consumed = response.parse(&raw_response[..20]).unwrap().consumed;
+        assert!(!response.is_complete());
+        response.parse(&raw_response[consumed..]).unwrap();
+        assert!(response.is_complete());
+    }
+
+    #[test]
+    fn parse_incomplete_headers_between_lines_response() {
+        let raw_response_first_part = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Server: Apache\r\n",
+            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
+        );
+        let raw_response = String::from(raw_response_first_part)
+            + "ETag: \"34aa387-d-1568eb00\"\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Incomplete,
+                consumed,
+                ..
+            }) if consumed == raw_response_first_part.len()
+        ));
+    }
+
+    #[test]
+    fn parse_incomplete_headers_mid_line_response() {
+        let raw_response_first_part = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+        );
+        let raw_response = String::from(raw_response_first_part)
+            + "Server: Apache\r\n"
+            + "Last-Modified: Wed, 22 Ju";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Incomplete,
+                consumed,
+                ..
+            }) if consumed == raw_response_first_part.len()
+        ));
+    }
+
+    #[test]
+    fn parse_incomplete_status_line() {
+        let raw_response = "HTTP/1.1 200 OK\r";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Incomplete,
+                consumed: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_status_line_one_byte_at_a_time() {
+        let reason_phrase = "X".repeat(500);
+        let raw_response = format!("HTTP/1.1 200 {reason_phrase}\r\n\r\n");
+        let raw_response = raw_response.as_bytes();
+        let mut response = Response::new();
+        let mut accepted = 0;
+        for i in 0..raw_response.len() {
+            let parse_results =
+                response.parse(&raw_response[accepted..=i]).unwrap();
+            accepted += parse_results.consumed;
+            if i + 1 < raw_response.len() {
+                assert_eq!(ParseStatus::Incomplete, parse_results.status);
+            } else {
+                assert_eq!(ParseStatus::Complete, parse_results.status);
+            }
+        }
+        assert_eq!(raw_response.len(), accepted);
+        assert_eq!(200, response.status_code);
+        assert_eq!(reason_phrase, response.reason_phrase);
+    }
+
+    #[test]
+    fn parse_invalid_partial_status_line_too_long() {
+        let raw_response = format!("HTTP/1.1 200 {}", "X".repeat(1000));
+        let raw_response = raw_response.as_bytes();
+        let mut response = Response::new();
+        let mut result = Ok(());
+        for i in 0..raw_response.len() {
+            if let Err(error) = response.parse(&raw_response[..=i]) {
+                assert_eq!(1000, i);
+                result = Err(error);
+                break;
+            }
+        }
+        assert!(matches!(
+            result,
+            Err(Error::StatusLineTooLong(line))
+                if line == raw_response[..1000]
+        ));
+    }
+
+    #[test]
+    fn parse_header_line_limit_defaults_to_match_request() {
+        let raw_response = format!(
+            "HTTP/1.1 200 OK\r\nX-Long: {}\r\n\r\n",
+            "X".repeat(1000)
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(&raw_response),
+            Err(Error::Headers(rhymessage::Error::HeaderLineTooLong(_)))
+        ));
+        let mut response = Response::new();
+        response.set_header_line_limit(Some(1010));
+        assert!(matches!(
+            response.parse(&raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_invalid_status_line_too_long() {
+        let raw_response = format!("HTTP/1.1 200 {}\r\n\r\n", "X".repeat(1000));
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(&raw_response),
+            Err(Error::StatusLineTooLong(line))
+                if line == raw_response.as_bytes()[..1000]
+        ));
+    }
+
+    #[test]
+    fn parse_long_status_line_no_limit() {
+        let raw_response = format!("HTTP/1.1 200 {}\r\n\r\n", "X".repeat(1000));
+        let mut response = Response::new();
+        response.status_line_limit = None;
+        assert!(matches!(
+            response.parse(&raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_no_headers_response() {
+        let raw_response = "HTTP/1.1 200 OK\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Incomplete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
+        ));
+    }
+
+    #[test]
+    fn parse_invalid_response_no_protocol() {
+        let raw_response = " 200 OK\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Err(Error::StatusLineProtocol(line)) if line == " 200 OK"
+        ));
+    }
+
+    #[test]
+    fn parse_and_generate_custom_protocol_only_if_accepted() {
+        let raw_response = "MYPROTO/1.0 200 OK\r\n\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Err(Error::StatusLineProtocol(line))
+                if line == "MYPROTO/1.0 200 OK"
+        ));
+        let mut response = Response::new();
+        response.accepted_protocols.push("MYPROTO/1.0".into());
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+        assert_eq!("MYPROTO/1.0", response.protocol);
+        assert!(matches!(
+            response.generate(),
+            Ok(output) if output == raw_response.as_bytes()
+        ));
+    }
+
+    #[test]
+    fn parse_invalid_response_no_status_code() {
+        let raw_response = "HTTP/1.1  OK\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Err(Error::InvalidStatusCode(_))
+        ));
+    }
+
+    #[test]
+    fn parse_skips_limited_empty_lines_between_pipelined_responses() {
+        let first_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 5\r\n",
+            "\r\n",
+            "Hello",
+        );
+        let second_response =
+            concat!("\r\n", "HTTP/1.1 204 No Content\r\n", "\r\n",);
+        let mut response = Response::new();
+        response.max_leading_empty_lines = 1;
+        assert!(matches!(
+            response.parse(first_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+        let mut input = Vec::new();
+        for piece in &["\r", "\n", &second_response[2..]] {
+            input.extend(piece.as_bytes());
+            let consumed = response.parse(&input).unwrap().consumed;
+            input.drain(..consumed);
+        }
+        assert!(input.is_empty());
+        assert_eq!(204, response.status_code);
+        let mut response = Response::new();
+        response.max_leading_empty_lines = 1;
+        assert!(matches!(
+            response.parse(second_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == second_response.len()
+        ));
+        assert_eq!(204, response.status_code);
+        let mut response = Response::new();
+        assert!(response.parse(second_response).is_err());
+        let mut response = Response::new();
+        response.max_leading_empty_lines = 1;
+        assert!(response.parse(format!("\r\n{second_response}")).is_err());
+    }
+
+    #[test]
+    fn parse_second_response_after_fixed_body() {
+        let first_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 5\r\n",
+            "\r\n",
+            "Hello",
+        );
+        let second_response = concat!(
+            "HTTP/1.1 404 Not Found\r\n",
+            "Content-Length: 3\r\n",
+            "\r\n",
+            "abc",
+        );
+        let input = format!("{first_response}{second_response}");
+        let mut response = Response::new();
+        let parse_results = response.parse(&input).unwrap();
+        assert_eq!(ParseStatus::Complete, parse_results.status);
+        assert_eq!(first_response.len(), parse_results.consumed);
+        assert_eq!(second_response.len(), parse_results.trailing);
+        assert_eq!(b"Hello", &response.body[..]);
+        assert!(response.trailer.is_empty());
+        let parse_results =
+            response.parse(&input[first_response.len()..]).unwrap();
+        assert_eq!(ParseStatus::Complete, parse_results.status);
+        assert_eq!(second_response.len(), parse_results.consumed);
+        assert_eq!(404, response.status_code);
+        assert_eq!(b"abc", &response.body[..]);
+        assert_eq!(
+            Some("3".into()),
+            response.headers.header_value("Content-Length")
+        );
+
+        // Only part of the second status line may have arrived with the
+        // first response.
+        let mut response = Response::new();
+        let parse_results =
+            response.parse(&input[..first_response.len() + 3]).unwrap();
+        assert_eq!(ParseStatus::Complete, parse_results.status);
+        assert_eq!(first_response.len(), parse_results.consumed);
+        assert!(response.trailer.is_empty());
+
+        // The same responses should come out if the input arrives one byte
+        // at a time, so the second status line is split up.
+        let mut responses = Vec::new();
+        let mut response = Response::new();
+        let mut buffer = Vec::new();
+        for byte in input.bytes() {
+            buffer.push(byte);
+            let parse_results = response.parse(&buffer).unwrap();
+            buffer.drain(..parse_results.consumed);
+            if parse_results.status == ParseStatus::Complete {
+                responses.push((response.status_code, response.body.clone()));
+            }
+        }
+        assert!(buffer.is_empty());
+        assert_eq!(
+            vec![(200, b"Hello".to_vec()), (404, b"abc".to_vec())],
+            responses
+        );
+    }
+
+    #[test]
+    fn parse_pipelined_responses_with_same_response() {
+        let first_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "Trailer: X-Checksum\r\n",
+            "\r\n",
+            "5\r\nHello\r\n",
+            "0\r\n",
+            "X-Checksum: 1234\r\n",
+            "\r\n",
+        );
+        let second_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 5\r\n",
+            "\r\n",
+            "Hello",
+        );
+        let third_response = concat!(
+            "HTTP/1.1 404 Not Found\r\n",
+            "Content-Length: 3\r\n",
+            "\r\n",
+            "abc",
         );
+        let mut input =
+            format!("{first_response}{second_response}{third_response}")
+                .into_bytes();
+        let mut response = Response::new();
+        let mut parsed = Vec::new();
+        while !input.is_empty() {
+            let parse_results = response.parse(&input).unwrap();
+            assert_eq!(ParseStatus::Complete, parse_results.status);
+            input.drain(..parse_results.consumed);
+            parsed.push((
+                response.status_code,
+                response.body.clone(),
+                response.headers.headers().len(),
+            ));
+        }
         assert_eq!(
-            "Hello World! My payload includes a trailing CRLF.\r\n".as_bytes(),
-            response.body
+            vec![
+                (200, b"Hello".to_vec(), 2),
+                (200, b"Hello".to_vec(), 1),
+                (404, b"abc".to_vec(), 1),
+            ],
+            parsed
         );
+        assert!(!response.headers.has_header("X-Checksum"));
+        assert!(response.trailer.is_empty());
     }
 
     #[test]
-    fn parse_get_response_with_chunked_body_no_other_transfer_coding() {
+    fn parse_at_eof_completes_chunked_body_missing_final_line() {
+        let head = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+            "5\r\nHello\r\n",
+        );
+        for (ending, trailer) in &[
+            ("0\r\n", None),
+            ("0\r\nX-Foo: Bar\r\n", Some("Bar")),
+            ("0\r\nX-Foo: Bar", Some("Bar")),
+        ] {
+            let raw_response = format!("{head}{ending}");
+            let mut response = Response::new();
+            assert!(matches!(
+                response.parse(&raw_response),
+                Ok(ParseResults {
+                    status: ParseStatus::Incomplete,
+                    ..
+                })
+            ));
+            let mut response = Response::new();
+            assert!(matches!(
+                response.parse_at_eof(&raw_response),
+                Ok(ParseResults {
+                    status: ParseStatus::Complete,
+                    consumed,
+                    trailing: 0,
+                    paused: false,
+                }) if consumed == raw_response.len()
+            ));
+            assert_eq!(b"Hello", &response.body[..]);
+            assert_eq!(
+                *trailer,
+                response.headers.header_value("X-Foo").as_deref()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_at_eof_leaves_truncated_body_incomplete() {
         let raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-            "Server: Apache\r\n",
-            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
-            "ETag: \"34aa387-d-1568eb00\"\r\n",
-            "Accept-Ranges: bytes\r\n",
             "Transfer-Encoding: chunked\r\n",
-            "Vary: Accept-Encoding\r\n",
-            "Content-Type: text/plain\r\n",
-            "Trailer: X-Foo\r\n",
             "\r\n",
-            "C\r\n",
-            "Hello World!\r\n",
-            "16\r\n",
-            " My payload includes a\r\n",
-            "11\r\n",
-            " trailing CRLF.\r\n\r\n",
-            "0\r\n",
-            "X-Foo: Bar\r\n",
+            "5\r\nHel",
+        );
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse_at_eof(raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Incomplete,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn stats_count_head_and_body_bytes() {
+        let head = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: chunked\r\n",
             "\r\n",
         );
+        let body = concat!("5\r\nHello\r\n", "0\r\n", "X-Foo: Bar\r\n", "\r\n");
         let mut response = Response::new();
         assert!(matches!(
-            response.parse(raw_response),
-            Ok(ParseResults{
+            response.parse(format!("{head}{body}")),
+            Ok(ParseResults {
                 status: ParseStatus::Complete,
-                consumed
-            }) if consumed == raw_response.len()
+                ..
+            })
         ));
-        assert_eq!(200, response.status_code);
-        assert_eq!("OK", response.reason_phrase);
-        assert!(response.headers.has_header("Date"));
         assert_eq!(
-            Some("Mon, 27 Jul 2009 12:28:53 GMT"),
-            response.headers.header_value("Date").as_deref()
+            MessageStats {
+                body_bytes: body.len(),
+                head_bytes: head.len(),
+            },
+            response.stats()
         );
-        assert!(response.headers.has_header("Accept-Ranges"));
-        assert_eq!(
-            Some("bytes"),
-            response.headers.header_value("Accept-Ranges").as_deref()
+    }
+
+    #[test]
+    fn parse_with_bare_line_feed_terminators_one_byte_at_a_time() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\n",
+            "Transfer-Encoding: chunked\n",
+            "\n",
+            "5\n",
+            "Hello\n",
+            "0\n",
+            "\n",
         );
-        assert!(response.headers.has_header("Content-Type"));
+        let mut response = Response::new();
+        response.line_terminator = LineTerminator::Lf;
+        let mut input = Vec::new();
+        let mut complete = false;
+        for byte in raw_response.bytes() {
+            assert!(!complete);
+            input.push(byte);
+            let parse_results = response.parse(&input).unwrap();
+            input.drain(..parse_results.consumed);
+            complete = parse_results.status == ParseStatus::Complete;
+        }
+        assert!(complete);
+        assert!(input.is_empty());
+        assert_eq!(200, response.status_code);
+        assert_eq!("OK", response.reason_phrase);
+        assert_eq!(b"Hello", &response.body[..]);
+    }
+
+    #[test]
+    fn generate_combines_repeated_headers_only_if_asked() {
+        let mut response = Response::new();
+        for (name, value) in &[
+            ("Via", "1.1 foo"),
+            ("Set-Cookie", "a=1"),
+            ("Via", "1.1 bar"),
+            ("Set-Cookie", "b=2"),
+        ] {
+            response.headers.add_header(Header {
+                name: (*name).into(),
+                value: (*value).into(),
+            });
+        }
+        let head = response.generate_head().unwrap();
         assert_eq!(
-            Some("text/plain"),
-            response.headers.header_value("Content-Type").as_deref()
+            concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Via: 1.1 foo\r\n",
+                "Set-Cookie: a=1\r\n",
+                "Via: 1.1 bar\r\n",
+                "Set-Cookie: b=2\r\n",
+                "\r\n",
+            ),
+            String::from_utf8(head).unwrap()
         );
+        response.combine_repeated_headers = true;
+        let head = response.generate_head().unwrap();
         assert_eq!(
-            Some("Bar"),
-            response.headers.header_value("X-Foo").as_deref()
+            concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Via: 1.1 foo, 1.1 bar\r\n",
+                "Set-Cookie: a=1\r\n",
+                "Set-Cookie: b=2\r\n",
+                "\r\n",
+            ),
+            String::from_utf8(head).unwrap()
         );
-        assert_eq!(
-            Some("51"),
-            response.headers.header_value("Content-Length").as_deref()
+        assert_eq!(4, response.headers.headers().len());
+    }
+
+    #[test]
+    fn status_code_classification() {
+        let mut response = Response::new();
+        let classes: &[(usize, [bool; 5])] = &[
+            (99, [false, false, false, false, false]),
+            (100, [true, false, false, false, false]),
+            (204, [false, true, false, false, false]),
+            (301, [false, false, true, false, false]),
+            (404, [false, false, false, true, false]),
+            (599, [false, false, false, false, true]),
+            (600, [false, false, false, false, false]),
+        ];
+        for (status_code, expected) in classes {
+            response.status_code = *status_code;
+            assert_eq!(
+                *expected,
+                [
+                    response.is_informational(),
+                    response.is_success(),
+                    response.is_redirect(),
+                    response.is_client_error(),
+                    response.is_server_error(),
+                ],
+                "{status_code}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_status_code_above_599_allowed_by_default() {
+        let raw_response = "HTTP/1.1 750 Custom\r\n\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+        assert_eq!(750, response.status_code);
+    }
+
+    #[test]
+    fn parse_status_code_out_of_range_when_strict() {
+        for (raw_response, code) in &[
+            ("HTTP/1.1 750 Custom\r\n\r\n", 750),
+            ("HTTP/1.1 099 Custom\r\n\r\n", 99),
+        ] {
+            let mut response = Response::new();
+            response.strict_status_codes = true;
+            assert!(matches!(
+                response.parse(*raw_response),
+                Err(Error::StatusCodeOutOfRange(actual)) if actual == *code
+            ));
+        }
+        for (raw_response, code) in &[
+            ("HTTP/1.1 100 Continue\r\n\r\n", 100),
+            ("HTTP/1.1 599 Custom\r\n\r\n", 599),
+        ] {
+            let mut response = Response::new();
+            response.strict_status_codes = true;
+            assert!(matches!(
+                response.parse(*raw_response),
+                Ok(ParseResults {
+                    status: ParseStatus::Complete,
+                    ..
+                })
+            ));
+            assert_eq!(*code, response.status_code);
+        }
+    }
+
+    #[test]
+    fn parse_invalid_response_no_reason_phrase() {
+        let raw_response = "HTTP/1.1 200\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Err(Error::StatusLineNoStatusCodeDelimiter(line))
+                if line == "HTTP/1.1 200"
+        ));
+    }
+
+    #[test]
+    fn parse_response_no_reason_phrase_when_allowed() {
+        for raw_response in
+            &["HTTP/1.1 204\r\n\r\n", "HTTP/1.1 204 \r\n\r\n"]
+        {
+            let mut response = Response::new();
+            response.reason_phrase = "Previous".into();
+            response.allow_missing_reason_phrase = true;
+            assert!(matches!(
+                response.parse(raw_response),
+                Ok(ParseResults {
+                    status: ParseStatus::Complete,
+                    ..
+                })
+            ));
+            assert_eq!(204, response.status_code);
+            assert_eq!("", response.reason_phrase);
+        }
+    }
+
+    #[test]
+    fn parse_invalid_damaged_header() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Server: Apache\r\n",
+            "Last-Modified Wed, 22 Jul 2009 19:15:56 GMT\r\n",
+            "ETag: \"34aa387-d-1568eb00\"\r\n",
+            "Accept-Ranges: bytes\r\n",
+            "Content-Length: 51\r\n",
+            "Vary: Accept-Encoding\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "Hello World! My payload includes a trailing CRLF.\r\n",
         );
-        assert_eq!(
-            "Hello World! My payload includes a trailing CRLF.\r\n".as_bytes(),
-            response.body
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(raw_response),
+            Err(Error::Headers(
+                rhymessage::Error::HeaderNameContainsIllegalCharacter(name)
+            )) if name == "Last-Modified Wed, 22 Jul 2009 19"
+        ));
+    }
+
+    #[test]
+    fn response_with_no_content_length_or_chunked_transfer_encoding_has_no_body(
+    ) {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Server: Apache\r\n",
+            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
+            "ETag: \"34aa387-d-1568eb00\"\r\n",
+            "Accept-Ranges: bytes\r\n",
+            "Vary: Accept-Encoding\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
         );
-        assert!(!response
-            .headers
-            .has_header_token("Transfer-Encoding", "chunked"));
-        assert!(!response.headers.has_header("Trailer"));
-        assert!(!response.headers.has_header("Transfer-Encoding"));
+        let trailer = "Hello World! My payload includes a trailing CRLF.\r\n";
+        let mut response = Response::new();
+        assert!(matches!(
+            response.parse(String::from(raw_response) + trailer),
+            Ok(ParseResults{
+                status: ParseStatus::Complete,
+                consumed,
+                trailing,
+                paused: false,
+            }) if consumed == raw_response.len() && trailing == trailer.len()
+        ));
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn parse_body_read_budget_pauses_chunked_body() {
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+            "5\r\nHello\r\n",
+            "5\r\nWorld\r\n",
+            "0\r\n\r\n",
+        );
+        let mut response = Response::new();
+        response.body_read_budget = Some(3);
+        let segments = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let observed_segments = segments.clone();
+        response.set_body_observer(move |segment| {
+            observed_segments.lock().unwrap().push(segment.to_vec());
+        });
+        let mut consumed = 0;
+        loop {
+            let parse_results =
+                response.parse(&raw_response[consumed..]).unwrap();
+            consumed += parse_results.consumed;
+            if parse_results.status == ParseStatus::Complete {
+                break;
+            }
+            assert!(parse_results.paused);
+        }
+        assert_eq!(raw_response.len(), consumed);
         assert_eq!(
-            "Hello World! My payload includes a trailing CRLF.\r\n".as_bytes(),
-            response.body
+            vec![
+                b"Hel".to_vec(),
+                b"loW".to_vec(),
+                b"orl".to_vec(),
+                b"d".to_vec(),
+            ],
+            *segments.lock().unwrap()
         );
+        assert_eq!(b"HelloWorld", &response.body[..]);
     }
 
     #[test]
-    fn parse_get_response_with_chunked_body_with_other_transfer_coding() {
+    fn parse_body_read_budget_keeps_trailer_after_fixed_body() {
         let raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-            "Transfer-Encoding: foobar, chunked\r\n",
-            "Content-Type: text/plain\r\n",
-            "\r\n",
-            "0\r\n",
+            "Content-Length: 5\r\n",
             "\r\n",
+            "Hello",
+            "Extra",
         );
         let mut response = Response::new();
+        response.body_read_budget = Some(5);
         assert!(matches!(
             response.parse(raw_response),
-            Ok(ParseResults{
+            Ok(ParseResults {
                 status: ParseStatus::Complete,
-                consumed
-            }) if consumed == raw_response.len()
+                ..
+            })
         ));
-        assert_eq!(
-            Some("foobar"),
-            response.headers.header_value("Transfer-Encoding").as_deref()
-        );
+        assert_eq!(b"Hello", &response.body[..]);
+        assert_eq!(b"Extra", &response.trailer[..]);
     }
 
     #[test]
-    fn parse_incomplete_body_response() {
+    fn parse_response_trailer() {
         let raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-            "Server: Apache\r\n",
-            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
-            "ETag: \"34aa387-d-1568eb00\"\r\n",
-            "Accept-Ranges: bytes\r\n",
-            "Content-Length: 52\r\n",
-            "Vary: Accept-Encoding\r\n",
+            "Content-Length: 18\r\n",
             "Content-Type: text/plain\r\n",
             "\r\n",
-            "Hello World! My payload includes a trailing CRLF.\r\n",
+            "This is the body\r\n",
+            "This is some extra trailer junk.",
         );
         let mut response = Response::new();
         assert!(matches!(
             response.parse(raw_response),
             Ok(ParseResults{
-                status: ParseStatus::Incomplete,
-                consumed
+                status: ParseStatus::Complete,
+                consumed,
+                ..
             }) if consumed == raw_response.len()
         ));
+        assert_eq!("This is the body\r\n".as_bytes(), response.body);
+        assert_eq!(
+            "This is some extra trailer junk.".as_bytes(),
+            response.trailer
+        );
     }
 
     #[test]
-    fn parse_incomplete_headers_between_lines_response() {
-        let raw_response_first_part = concat!(
+    fn parse_response_trailing_data_rejected_when_strict() {
+        let raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-            "Server: Apache\r\n",
-            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
+            "Content-Length: 5\r\n",
+            "\r\n",
+            "Hello",
+            "Junk",
         );
-        let raw_response = String::from(raw_response_first_part)
-            + "ETag: \"34aa387-d-1568eb00\"\r\n";
         let mut response = Response::new();
+        response.reject_trailing_data = true;
         assert!(matches!(
             response.parse(raw_response),
+            Err(Error::UnexpectedTrailingData(4))
+        ));
+        let mut response = Response::new();
+        response.reject_trailing_data = true;
+        assert!(matches!(
+            response.parse(&raw_response[..raw_response.len() - 4]),
             Ok(ParseResults{
-                status: ParseStatus::Incomplete,
-                consumed
-            }) if consumed == raw_response_first_part.len()
+                status: ParseStatus::Complete,
+                ..
+            })
         ));
+        assert!(response.trailer.is_empty());
     }
 
     #[test]
-    fn parse_incomplete_headers_mid_line_response() {
-        let raw_response_first_part = concat!(
+    fn framing_reports_parser_decision() {
+        let mut head_request = Request::new();
+        head_request.method = "HEAD".into();
+        for (request, raw_response, framing) in &[
+            (
+                Request::new(),
+                "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello",
+                Framing::FixedLength(5),
+            ),
+            (
+                Request::new(),
+                concat!(
+                    "HTTP/1.1 200 OK\r\n",
+                    "Transfer-Encoding: chunked\r\n",
+                    "\r\n",
+                    "0\r\n\r\n",
+                ),
+                Framing::Chunked,
+            ),
+            (Request::new(), "HTTP/1.1 200 OK\r\n\r\n", Framing::UntilClose),
+            (
+                head_request,
+                "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+                Framing::None,
+            ),
+        ] {
+            let mut response = Response::new();
+            assert_eq!(Framing::None, response.framing());
+            assert!(matches!(
+                response.parse_for_request(request, raw_response),
+                Ok(ParseResults {
+                    status: ParseStatus::Complete,
+                    ..
+                })
+            ));
+            assert_eq!(*framing, response.framing());
+        }
+    }
+
+    #[test]
+    fn parse_for_request_head_response_has_no_body() {
+        let mut request = Request::new();
+        request.method = "HEAD".into();
+        let raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
+            "Content-Length: 18\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
         );
-        let raw_response = String::from(raw_response_first_part)
-            + "Server: Apache\r\n"
-            + "Last-Modified: Wed, 22 Ju";
         let mut response = Response::new();
         assert!(matches!(
-            response.parse(raw_response),
+            response.parse_for_request(&request, raw_response),
             Ok(ParseResults{
-                status: ParseStatus::Incomplete,
-                consumed
-            }) if consumed == raw_response_first_part.len()
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
         ));
+        assert!(response.body.is_empty());
+        assert_eq!(
+            Some("18"),
+            response.headers.header_value("Content-Length").as_deref()
+        );
     }
 
     #[test]
-    fn parse_incomplete_status_line() {
-        let raw_response = "HTTP/1.1 200 OK\r";
+    fn parse_for_request_successful_connect_response_has_no_body() {
+        let mut request = Request::new();
+        request.method = "CONNECT".into();
+        let raw_response = concat!(
+            "HTTP/1.1 200 Connection Established\r\n",
+            "Transfer-Encoding: chunked\r\n",
+            "\r\n",
+        );
+        let tunnel = "This is the first thing sent through the tunnel";
         let mut response = Response::new();
         assert!(matches!(
-            response.parse(raw_response),
-            Ok(ParseResults {
-                status: ParseStatus::Incomplete,
-                consumed: 0
-            })
+            response.parse_for_request(
+                &request,
+                String::from(raw_response) + tunnel
+            ),
+            Ok(ParseResults{
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
         ));
+        assert!(response.body.is_empty());
     }
 
     #[test]
-    fn parse_no_headers_response() {
-        let raw_response = "HTTP/1.1 200 OK\r\n";
+    fn parse_for_request_failed_connect_response_has_body() {
+        let mut request = Request::new();
+        request.method = "CONNECT".into();
+        let raw_response = concat!(
+            "HTTP/1.1 403 Forbidden\r\n",
+            "Content-Length: 4\r\n",
+            "\r\n",
+            "Nope",
+        );
         let mut response = Response::new();
         assert!(matches!(
-            response.parse(raw_response),
+            response.parse_for_request(&request, raw_response),
             Ok(ParseResults{
-                status: ParseStatus::Incomplete,
-                consumed
+                status: ParseStatus::Complete,
+                consumed,
+                ..
             }) if consumed == raw_response.len()
         ));
+        assert_eq!("Nope".as_bytes(), response.body);
     }
 
     #[test]
-    fn parse_invalid_response_no_protocol() {
-        let raw_response = " 200 OK\r\n";
+    fn parse_for_request_no_body_status_codes() {
+        let request = Request::new();
+        for status_line in &[
+            "HTTP/1.1 100 Continue\r\n",
+            "HTTP/1.1 204 No Content\r\n",
+            "HTTP/1.1 304 Not Modified\r\n",
+        ] {
+            let raw_response =
+                String::from(*status_line) + "Content-Length: 4\r\n\r\n";
+            let mut response = Response::new();
+            assert!(
+                matches!(
+                    response.parse_for_request(
+                        &request,
+                        raw_response.clone() + "Nope"
+                    ),
+                    Ok(ParseResults{
+                        status: ParseStatus::Complete,
+                        consumed,
+                        ..
+                    }) if consumed == raw_response.len()
+                ),
+                "{}",
+                status_line
+            );
+            assert!(response.body.is_empty(), "{}", status_line);
+        }
+    }
+
+    #[test]
+    fn parse_for_request_get_response_has_body() {
+        let request = Request::new();
+        let raw_response = concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Length: 18\r\n",
+            "\r\n",
+            "This is the body\r\n",
+        );
         let mut response = Response::new();
         assert!(matches!(
-            response.parse(raw_response),
-            Err(Error::StatusLineProtocol(line)) if line == " 200 OK"
+            response.parse_for_request(&request, raw_response),
+            Ok(ParseResults{
+                status: ParseStatus::Complete,
+                consumed,
+                ..
+            }) if consumed == raw_response.len()
         ));
+        assert_eq!("This is the body\r\n".as_bytes(), response.body);
     }
 
     #[test]
-    fn parse_invalid_response_no_status_code() {
-        let raw_response = "HTTP/1.1  OK\r\n";
+    fn parse_for_request_rules_apply_only_to_that_call() {
+        let mut request = Request::new();
+        request.method = "HEAD".into();
         let mut response = Response::new();
-        assert!(matches!(
-            response.parse(raw_response),
-            Err(Error::InvalidStatusCode(_))
-        ));
+        let parse_results = response
+            .parse_for_request(
+                &request,
+                "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+            )
+            .unwrap();
+        assert_eq!(ParseStatus::Complete, parse_results.status);
+        let raw_response = "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello";
+        let parse_results = response.parse(raw_response).unwrap();
+        assert_eq!(ParseStatus::Complete, parse_results.status);
+        assert_eq!(raw_response.len(), parse_results.consumed);
+        assert_eq!(b"Hello", &response.body[..]);
     }
 
     #[test]
-    fn parse_invalid_response_no_reason_phrase() {
-        let raw_response = "HTTP/1.1 200\r\n";
+    fn parse_for_http_1_0_request_reads_body_until_close() {
+        let mut request = Request::new();
+        request.protocol = "HTTP/1.0".into();
+        let mut response = Response::new();
+        let parse_results = response
+            .parse_for_request(&request, "HTTP/1.1 200 OK\r\n\r\nHello")
+            .unwrap();
+        assert_eq!(ParseStatus::Incomplete, parse_results.status);
+        assert_eq!(Framing::UntilClose, response.framing());
+        assert!(response.parse_for_request(&request, ", World!").is_ok());
+        response.finalize().unwrap();
+        assert_eq!(b"Hello, World!", &response.body[..]);
+        let mut response = Response::new();
+        let parse_results = response
+            .parse_for_request(&Request::new(), "HTTP/1.1 200 OK\r\n\r\n")
+            .unwrap();
+        assert_eq!(ParseStatus::Complete, parse_results.status);
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn parse_response_max_message_size() {
+        let raw_head = "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n";
         let mut response = Response::new();
+        response.max_message_size = Some(raw_head.len() - 1);
         assert!(matches!(
-            response.parse(raw_response),
-            Err(Error::StatusLineNoStatusCodeDelimiter(line))
-                if line == "HTTP/1.1 200"
+            response.parse(raw_head),
+            Err(Error::MessageTooLong)
         ));
+        let mut response = Response::new();
+        response.max_message_size = Some(raw_head.len() + 9);
+        assert!(matches!(
+            response.parse(raw_head),
+            Err(Error::MessageTooLong)
+        ));
+        let mut response = Response::new();
+        response.max_message_size = Some(raw_head.len() + 10);
+        let parse_results =
+            response.parse(format!("{raw_head}0123456789")).unwrap();
+        assert_eq!(ParseStatus::Complete, parse_results.status);
+        assert!(!response.body_truncated());
     }
 
     #[test]
-    fn parse_invalid_damaged_header() {
+    fn parse_response_oversize_body_truncated() {
+        for (raw_head, raw_body) in &[
+            ("HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n", "0123456789"),
+            (
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+                "a\r\n0123456789\r\n0\r\n\r\n",
+            ),
+        ] {
+            let mut response = Response::new();
+            response.max_message_size = Some(raw_head.len() + 4);
+            response.on_oversize_body = OnOversizeBody::Truncate;
+            let parse_results =
+                response.parse(format!("{raw_head}{raw_body}")).unwrap();
+            assert_eq!(ParseStatus::Complete, parse_results.status);
+            assert_eq!(b"0123", &response.body[..]);
+            assert!(response.body_truncated());
+        }
+        let raw_head = "HTTP/1.1 200 OK\r\n\r\n";
+        let mut response = Response::new();
+        response.set_close_delimited(true);
+        response.max_message_size = Some(raw_head.len() + 3);
+        response.on_oversize_body = OnOversizeBody::Truncate;
+        assert!(response.parse(format!("{raw_head}Hello")).is_ok());
+        assert!(response.finalize().is_ok());
+        assert_eq!(b"Hel", &response.body[..]);
+        assert!(response.body_truncated());
+    }
+
+    #[test]
+    fn parse_response_too_many_chunks() {
         let raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-            "Server: Apache\r\n",
-            "Last-Modified Wed, 22 Jul 2009 19:15:56 GMT\r\n",
-            "ETag: \"34aa387-d-1568eb00\"\r\n",
-            "Accept-Ranges: bytes\r\n",
-            "Content-Length: 51\r\n",
-            "Vary: Accept-Encoding\r\n",
-            "Content-Type: text/plain\r\n",
+            "Transfer-Encoding: chunked\r\n",
             "\r\n",
-            "Hello World! My payload includes a trailing CRLF.\r\n",
+            "1\r\nX\r\n",
+            "1\r\nY\r\n",
+            "1\r\nZ\r\n",
+            "0\r\n\r\n",
         );
         let mut response = Response::new();
+        response.max_chunk_count = Some(2);
         assert!(matches!(
             response.parse(raw_response),
-            Err(Error::Headers(
-                rhymessage::Error::HeaderNameContainsIllegalCharacter(name)
-            )) if name == "Last-Modified Wed, 22 Jul 2009 19"
+            Err(Error::TooManyChunks)
         ));
     }
 
     #[test]
-    fn response_with_no_content_length_or_chunked_transfer_encoding_has_no_body(
-    ) {
+    fn parse_chunk_extensions_too_long() {
         let raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Date: Mon, 27 Jul 2009 12:28:53 GMT\r\n",
-            "Server: Apache\r\n",
-            "Last-Modified: Wed, 22 Jul 2009 19:15:56 GMT\r\n",
-            "ETag: \"34aa387-d-1568eb00\"\r\n",
-            "Accept-Ranges: bytes\r\n",
-            "Vary: Accept-Encoding\r\n",
-            "Content-Type: text/plain\r\n",
+            "Transfer-Encoding: chunked\r\n",
             "\r\n",
+            "1;a=b\r\nX\r\n",
+            "1;a=0123456789\r\nY\r\n",
+            "0\r\n\r\n",
         );
-        let trailer = "Hello World! My payload includes a trailing CRLF.\r\n";
         let mut response = Response::new();
+        response.max_chunk_ext_bytes = Some(4);
         assert!(matches!(
-            response.parse(String::from(raw_response) + trailer),
-            Ok(ParseResults{
+            response.parse(raw_response),
+            Err(Error::ChunkExtensionTooLong)
+        ));
+        let mut response = Response::new();
+        response.max_chunk_ext_bytes = Some(4);
+        assert!(matches!(
+            response.parse(concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Transfer-Encoding: chunked\r\n",
+                "\r\n",
+                "1;a=0123456789",
+            )),
+            Err(Error::ChunkExtensionTooLong)
+        ));
+        let mut response = Response::new();
+        response.max_chunk_ext_bytes = Some(13);
+        assert!(matches!(
+            response.parse(raw_response),
+            Ok(ParseResults {
                 status: ParseStatus::Complete,
-                consumed
-            }) if consumed == raw_response.len()
+                ..
+            })
         ));
-        assert!(response.body.is_empty());
+        assert_eq!(b"XY", &response.body[..]);
     }
 
     #[test]
-    fn parse_response_trailer() {
-        let raw_response = concat!(
+    fn parse_decodes_content_encoding_after_chunked_body() {
+        let encoded_body = [
+            0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0A, 0xF3,
+            0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0x08, 0xCF, 0x2F, 0xCA, 0x49,
+            0x51, 0x04, 0x00, 0xD0, 0xC3, 0x4A, 0xEC, 0x0D, 0x00, 0x00, 0x00,
+        ];
+        let mut raw_response = concat!(
             "HTTP/1.1 200 OK\r\n",
-            "Content-Length: 18\r\n",
-            "Content-Type: text/plain\r\n",
+            "Content-Encoding: gzip\r\n",
+            "Transfer-Encoding: chunked\r\n",
             "\r\n",
-            "This is the body\r\n",
-            "This is some extra trailer junk.",
+            "21\r\n",
+        )
+        .as_bytes()
+        .to_vec();
+        raw_response.extend(encoded_body);
+        raw_response.extend(b"\r\n0\r\n\r\n");
+        let mut response = Response::new();
+        response.decode_content_encoding = true;
+        assert!(matches!(
+            response.parse(&raw_response),
+            Ok(ParseResults {
+                status: ParseStatus::Complete,
+                ..
+            })
+        ));
+        assert_eq!(b"Hello, World!", &response.body[..]);
+        assert!(!response.headers.has_header("Content-Encoding"));
+        assert_eq!(
+            Some("13"),
+            response.headers.header_value("Content-Length").as_deref()
         );
         let mut response = Response::new();
+        response.decode_content_encoding = true;
+        response.max_decoded_body_size = Some(12);
         assert!(matches!(
-            response.parse(raw_response),
-            Ok(ParseResults{
+            response.parse(&raw_response),
+            Err(Error::DecodedBodyTooLarge)
+        ));
+        let mut response = Response::new();
+        assert!(response.parse(&raw_response).is_ok());
+        assert_eq!(&encoded_body[..], &response.body[..]);
+    }
+
+    #[test]
+    fn close_delimited_round_trip() {
+        let mut response = Response::new();
+        response.headers.set_header("Content-Type", "text/plain");
+        response.headers.set_header("Transfer-Encoding", "chunked");
+        response.set_body("Hello, World!");
+        response.set_close_delimited(true);
+        assert!(response.is_close_delimited());
+        let raw_response = response.generate().unwrap();
+        assert_eq!(
+            &b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: \
+               close\r\n\r\nHello, World!"[..],
+            &raw_response[..]
+        );
+        let mut parsed = Response::new();
+        parsed.set_close_delimited(true);
+        let (head, body) = raw_response.split_at(raw_response.len() - 6);
+        assert!(matches!(
+            parsed.parse(head),
+            Ok(ParseResults {
+                status: ParseStatus::Incomplete,
+                consumed,
+                ..
+            }) if consumed == head.len()
+        ));
+        assert_eq!(ParsePhase::Body, parsed.parse_phase());
+        assert_eq!(Framing::UntilClose, parsed.framing());
+        assert!(matches!(
+            parsed.parse_at_eof(body),
+            Ok(ParseResults {
                 status: ParseStatus::Complete,
-                consumed
-            }) if consumed == raw_response.len()
+                consumed: 6,
+                trailing: 0,
+                ..
+            })
         ));
-        assert_eq!("This is the body\r\n".as_bytes(), response.body);
+        assert!(parsed.is_complete());
+        assert_eq!(ParsePhase::StatusLine, parsed.parse_phase());
+        assert_eq!(b"Hello, World!", &parsed.body[..]);
+        assert_eq!(raw_response, parsed.generate().unwrap());
+    }
+
+    #[test]
+    fn finalize_response() {
+        let mut response = Response::new();
+        assert!(response.parse("HTTP/1.1 200 OK\r\n\r\nHello").is_ok());
+        assert!(response.is_complete());
+        assert!(response.body.is_empty());
+        assert!(response.finalize().is_ok());
+        let mut response = Response::new();
+        response.set_close_delimited(true);
+        assert!(response.parse("HTTP/1.1 200 OK\r\n").is_ok());
+        assert!(matches!(response.finalize(), Err(Error::IncompleteMessage)));
+        assert!(response.parse("\r\nHello").is_ok());
+        assert!(response.finalize().is_ok());
+        assert_eq!(b"Hello", &response.body[..]);
+    }
+
+    #[test]
+    fn vary_round_trip() {
+        let mut response = Response::new();
+        assert!(response.vary().is_empty());
+        response.set_vary(&["Accept", " accept ", "", "Origin"]);
         assert_eq!(
-            "This is some extra trailer junk.".as_bytes(),
-            response.trailer
+            Some("Accept, Origin"),
+            response.headers.header_value("Vary").as_deref()
+        );
+        assert_eq!(vec!["Accept", "Origin"], response.vary());
+        response.headers.add_header(Header {
+            name: "Vary".into(),
+            value: "User-Agent, *".into(),
+        });
+        assert_eq!(vec!["*"], response.vary());
+        response.set_vary(&["Accept", "*"]);
+        assert_eq!(vec!["*"], header_values(&response.headers, "Vary"));
+        response.set_vary(&[]);
+        assert!(!response.headers.has_header("Vary"));
+    }
+
+    #[test]
+    fn generate_headers_only() {
+        let mut response = Response::new();
+        response.headers.set_header("Transfer-Encoding", "chunked");
+        response.headers.set_header("X-Words", "alpha beta gamma");
+        response.body = "Hello, World!".into();
+        let raw_headers = response.generate_headers().unwrap();
+        assert_eq!(
+            concat!(
+                "Transfer-Encoding: chunked\r\n",
+                "X-Words: alpha beta gamma\r\n",
+                "\r\n",
+            )
+            .as_bytes(),
+            &raw_headers[..]
         );
+        assert!(response.generate().unwrap().ends_with(b"0\r\n\r\n"));
+        response.set_header_line_limit(Some(18));
+        assert!(matches!(
+            response.generate_headers(),
+            Err(Error::Headers(_))
+        ));
     }
 }
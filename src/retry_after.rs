@@ -0,0 +1,76 @@
+use super::http_date::parse_http_date;
+use std::time::SystemTime;
+
+/// This represents the value of a `Retry-After` header, which indicates how
+/// long a client ought to wait before making a follow-up request, as defined
+/// in [IETF RFC 7231 section
+/// 7.1.3](https://tools.ietf.org/html/rfc7231#section-7.1.3).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RetryAfter {
+    /// The client ought to wait until the attached time.
+    Date(SystemTime),
+
+    /// The client ought to wait the attached number of seconds.
+    Seconds(u64),
+}
+
+impl RetryAfter {
+    /// Parse the given text, such as the value of a `Retry-After` header,
+    /// which may be either a number of seconds or an HTTP-date, in the
+    /// preferred IMF-fixdate format or either of the obsolete formats
+    /// described in [IETF RFC 7231 section
+    /// 7.1.1.1](https://tools.ietf.org/html/rfc7231#section-7.1.1.1).
+    /// `None` is returned if the text is neither.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate rhymuweb;
+    /// use rhymuweb::RetryAfter;
+    /// use std::time::{
+    ///     Duration,
+    ///     SystemTime,
+    /// };
+    ///
+    /// assert_eq!(Some(RetryAfter::Seconds(120)), RetryAfter::parse("120"));
+    /// assert_eq!(
+    ///     Some(RetryAfter::Date(
+    ///         SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777)
+    ///     )),
+    ///     RetryAfter::parse("Sun, 06 Nov 1994 08:49:37 GMT")
+    /// );
+    /// assert_eq!(None, RetryAfter::parse("soon"));
+    /// ```
+    #[must_use]
+    pub fn parse<T>(text: T) -> Option<Self>
+    where
+        T: AsRef<str>,
+    {
+        let text = text.as_ref().trim();
+        if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) {
+            text.parse().ok().map(RetryAfter::Seconds)
+        } else {
+            parse_http_date(text).map(RetryAfter::Date)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_retry_after() {
+        assert_eq!(Some(RetryAfter::Seconds(0)), RetryAfter::parse("0"));
+        assert_eq!(Some(RetryAfter::Seconds(30)), RetryAfter::parse(" 30 "));
+        assert!(matches!(
+            RetryAfter::parse("Tue, 29 Feb 2000 00:00:00 GMT"),
+            Some(RetryAfter::Date(_))
+        ));
+        assert_eq!(None, RetryAfter::parse(""));
+        assert_eq!(None, RetryAfter::parse("-5"));
+        assert_eq!(None, RetryAfter::parse("1.5"));
+        assert_eq!(None, RetryAfter::parse("99999999999999999999999"));
+    }
+}
@@ -0,0 +1,121 @@
+use rhymessage::MessageHeaders;
+
+/// This enumerates the ways in which the headers found in the trailer of a
+/// body which uses the "chunked" transfer coding may be merged into the
+/// headers of the message, when a trailer header has the same name as a
+/// header already in the message.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TrailerMergeStrategy {
+    /// Trailer headers are added after the existing headers, even if headers
+    /// with the same name are already present.  This is the default.
+    #[default]
+    Append,
+
+    /// Existing headers with the same name as a trailer header are removed
+    /// before the trailer headers are added.
+    Overwrite,
+
+    /// Trailer headers with the same name as an existing header are
+    /// discarded.
+    Ignore,
+}
+
+impl TrailerMergeStrategy {
+    // Move the given trailer headers into the given message headers,
+    // resolving any names they have in common according to the strategy.
+    pub(crate) fn merge(
+        self,
+        headers: &mut MessageHeaders,
+        trailer: MessageHeaders,
+    ) {
+        match self {
+            TrailerMergeStrategy::Append => {},
+            TrailerMergeStrategy::Overwrite => {
+                for header in &trailer {
+                    headers.remove_header(&header.name);
+                }
+            },
+            TrailerMergeStrategy::Ignore => {
+                let new_headers = trailer
+                    .into_iter()
+                    .filter(|header| !headers.has_header(&header.name))
+                    .collect::<Vec<_>>();
+                for header in new_headers {
+                    headers.add_header(header);
+                }
+                return;
+            },
+        }
+        for header in trailer {
+            headers.add_header(header);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rhymessage::Header;
+
+    fn merge_with(strategy: TrailerMergeStrategy) -> Vec<(String, String)> {
+        let mut headers = MessageHeaders::new();
+        headers.add_header(Header {
+            name: "X-Foo".into(),
+            value: "Bar".into(),
+        });
+        headers.add_header(Header {
+            name: "X-Foo".into(),
+            value: "Baz".into(),
+        });
+        headers.set_header("X-Spam", "Eggs");
+        let mut trailer = MessageHeaders::new();
+        trailer.set_header("x-foo", "Trailer");
+        trailer.set_header("X-Checksum", "12345");
+        strategy.merge(&mut headers, trailer);
+        headers
+            .headers()
+            .iter()
+            .map(|header| (header.name.to_string(), header.value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn merge_trailer_append() {
+        assert_eq!(
+            vec![
+                ("X-Foo".to_string(), "Bar".to_string()),
+                ("X-Foo".to_string(), "Baz".to_string()),
+                ("X-Spam".to_string(), "Eggs".to_string()),
+                ("x-foo".to_string(), "Trailer".to_string()),
+                ("X-Checksum".to_string(), "12345".to_string()),
+            ],
+            merge_with(TrailerMergeStrategy::Append)
+        );
+    }
+
+    #[test]
+    fn merge_trailer_overwrite() {
+        assert_eq!(
+            vec![
+                ("X-Spam".to_string(), "Eggs".to_string()),
+                ("x-foo".to_string(), "Trailer".to_string()),
+                ("X-Checksum".to_string(), "12345".to_string()),
+            ],
+            merge_with(TrailerMergeStrategy::Overwrite)
+        );
+    }
+
+    #[test]
+    fn merge_trailer_ignore() {
+        assert_eq!(
+            vec![
+                ("X-Foo".to_string(), "Bar".to_string()),
+                ("X-Foo".to_string(), "Baz".to_string()),
+                ("X-Spam".to_string(), "Eggs".to_string()),
+                ("X-Checksum".to_string(), "12345".to_string()),
+            ],
+            merge_with(TrailerMergeStrategy::Ignore)
+        );
+    }
+}